@@ -0,0 +1,467 @@
+//! Renders pass barcodes in their own symbology, and reads QR and Aztec codes back.
+
+mod aztec;
+mod code128;
+mod error;
+mod pdf417;
+mod qr;
+
+use image::{GrayImage, Luma};
+use qrcode::{types::QrError, Color, EcLevel, QrCode};
+
+use crate::pass::BarcodeFormat;
+
+pub use error::BarcodeError;
+
+/// How much of a symbol is spent on error correction. Code 128 has none, so ignores this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorCorrection {
+    Low,
+    #[default]
+    Medium,
+    Quartile,
+    High,
+}
+
+impl ErrorCorrection {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "l" | "low" => Some(Self::Low),
+            "m" | "medium" => Some(Self::Medium),
+            "q" | "quartile" => Some(Self::Quartile),
+            "h" | "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    fn qr_level(self) -> EcLevel {
+        match self {
+            Self::Low => EcLevel::L,
+            Self::Medium => EcLevel::M,
+            Self::Quartile => EcLevel::Q,
+            Self::High => EcLevel::H,
+        }
+    }
+
+    /// PDF417 error correction levels above the one recommended for the message's size.
+    fn pdf417_extra_levels(self) -> usize {
+        match self {
+            Self::Low => 0,
+            Self::Medium => 1,
+            Self::Quartile => 2,
+            Self::High => 3,
+        }
+    }
+
+    /// Share of an Aztec symbol, in percent, reserved for check words.
+    fn aztec_percent(self) -> usize {
+        match self {
+            Self::Low => 10,
+            Self::Medium => 23,
+            Self::Quartile => 36,
+            Self::High => 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Blank modules around the symbol. Defaults to what each symbology asks for.
+    pub quiet_zone: Option<u32>,
+    /// Pixels per module.
+    pub module_size: u32,
+    pub error_correction: ErrorCorrection,
+    /// Height of Code 128 bars, in modules.
+    pub bar_height: u32,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            quiet_zone: None,
+            module_size: 4,
+            error_correction: ErrorCorrection::default(),
+            bar_height: 50,
+        }
+    }
+}
+
+/// Dark and light modules of a symbol, row by row.
+#[derive(Debug, Clone)]
+pub struct BitMatrix {
+    width: usize,
+    height: usize,
+    modules: Vec<bool>,
+}
+
+impl BitMatrix {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            modules: vec![false; width * height],
+        }
+    }
+
+    /// Repeats `row` `height` times, for linear symbols.
+    fn from_row(row: &[bool], height: u32) -> Self {
+        Self {
+            width: row.len(),
+            height: height as usize,
+            modules: row.repeat(height as usize),
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize) {
+        self.modules[y * self.width + x] = true;
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.width + x]
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Encodes `message` in the IANA character set named by `encoding`, as pass.json's
+/// `messageEncoding` asks.
+pub fn encode_message(message: &str, encoding: &str) -> Result<Vec<u8>, BarcodeError> {
+    // Browsers treat latin1 as windows-1252, which encoding_rs follows. Wallets don't.
+    if matches!(encoding.trim().to_ascii_lowercase().as_str(), "iso-8859-1" | "iso8859-1" | "latin1" | "l1") {
+        return message.chars()
+            .map(|character| u8::try_from(character).map_err(|_| BarcodeError::Unrepresentable {
+                encoding: "iso-8859-1".to_owned(),
+                character,
+            }))
+            .collect();
+    }
+
+    let Some(charset) = encoding_rs::Encoding::for_label(encoding.trim().as_bytes()) else {
+        return Err(BarcodeError::UnknownEncoding(encoding.to_owned()));
+    };
+    // UTF-16 and a few others can only be decoded, and encode as UTF-8 instead.
+    if charset.output_encoding() != charset {
+        return Err(BarcodeError::UnknownEncoding(encoding.to_owned()));
+    }
+    let (bytes, _, had_errors) = charset.encode(message);
+    if had_errors {
+        let mut buffer = [0; 4];
+        let character = message.chars()
+            .find(|c| charset.encode(c.encode_utf8(&mut buffer)).2)
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        return Err(BarcodeError::Unrepresentable {
+            encoding: charset.name().to_owned(),
+            character,
+        });
+    }
+
+    Ok(bytes.into_owned())
+}
+
+pub fn encode(format: BarcodeFormat, data: &[u8], options: &RenderOptions) -> Result<BitMatrix, BarcodeError> {
+    let matrix = match format {
+        BarcodeFormat::Qr => {
+            let code = QrCode::with_error_correction_level(data, options.error_correction.qr_level()).map_err(|e| match e {
+                QrError::DataTooLong => BarcodeError::TooLong(BarcodeFormat::Qr),
+                e => BarcodeError::Qr(e),
+            })?;
+            let width = code.width();
+            let mut matrix = BitMatrix::new(width, width);
+            for (idx, color) in code.to_colors().into_iter().enumerate() {
+                if color == Color::Dark {
+                    matrix.set(idx % width, idx / width);
+                }
+            }
+            matrix
+        },
+        BarcodeFormat::Aztec => aztec::encode(data, options.error_correction.aztec_percent())?,
+        BarcodeFormat::Code128 => code128::encode(data, options.bar_height),
+        BarcodeFormat::Pdf417 => pdf417::encode(data, options.error_correction.pdf417_extra_levels())?,
+    };
+
+    Ok(matrix)
+}
+
+/// Renders `message` as `format`, with the message encoded as `encoding` first.
+pub fn render(format: BarcodeFormat, message: &str, encoding: &str, options: &RenderOptions) -> Result<GrayImage, BarcodeError> {
+    let data = encode_message(message, encoding)?;
+    let matrix = encode(format, &data, options)?;
+
+    let quiet_zone = options.quiet_zone.unwrap_or(match format {
+        BarcodeFormat::Qr => 4,
+        BarcodeFormat::Aztec | BarcodeFormat::Pdf417 => 2,
+        BarcodeFormat::Code128 => 10,
+    }) as usize;
+    let module_size = options.module_size.max(1) as usize;
+    let width = (matrix.width() + quiet_zone * 2) * module_size;
+    let height = (matrix.height() + quiet_zone * 2) * module_size;
+
+    let image = GrayImage::from_fn(width as u32, height as u32, |x, y| {
+        let x = (x as usize / module_size).checked_sub(quiet_zone).filter(|&x| x < matrix.width());
+        let y = (y as usize / module_size).checked_sub(quiet_zone).filter(|&y| y < matrix.height());
+        match (x, y) {
+            (Some(x), Some(y)) if matrix.get(x, y) => Luma([0]),
+            _ => Luma([255]),
+        }
+    });
+
+    Ok(image)
+}
+
+/// Dark pixels of a picture of a barcode, split at the midpoint of its luma range.
+struct Picture<'a> {
+    image: &'a GrayImage,
+    threshold: u8,
+}
+
+impl Picture<'_> {
+    fn dark(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && x < self.image.width() as i64 && y < self.image.height() as i64
+            && self.image.get_pixel(x as u32, y as u32).0[0] < self.threshold
+    }
+
+    /// Where the color changes for the `count`th time walking from `(x, y)` by `(dx, dy)`.
+    fn transition(&self, (x, y): (i64, i64), (dx, dy): (i64, i64), count: usize) -> Option<i64> {
+        let mut color = self.dark(x, y);
+        let mut seen = 0;
+        let (mut x, mut y) = (x, y);
+        while x >= 0 && y >= 0 && x < self.image.width() as i64 && y < self.image.height() as i64 {
+            if self.dark(x, y) != color {
+                color = !color;
+                seen += 1;
+                if seen == count {
+                    return Some(if dx != 0 { x } else { y });
+                }
+            }
+            x += dx;
+            y += dy;
+        }
+        None
+    }
+
+    /// Runs of one color along `count` pixels from `from` by `step`, as where each starts along
+    /// the line, how long it is and whether it's dark.
+    fn runs(&self, (x, y): (i64, i64), (dx, dy): (i64, i64), count: i64) -> Vec<(i64, i64, bool)> {
+        let mut runs: Vec<(i64, i64, bool)> = vec![];
+        for idx in 0..count {
+            let dark = self.dark(x + dx * idx, y + dy * idx);
+            match runs.last_mut() {
+                Some(run) if run.2 == dark => run.1 += 1,
+                _ => runs.push((idx, 1, dark)),
+            }
+        }
+        runs
+    }
+
+    /// Candidates for the center of an Aztec bulls eye on row `y` of `bounds`. A line through
+    /// the center module crosses seven runs of about the same width, the middle one dark, between
+    /// the dark sides of the outermost ring, and so does the column through it.
+    fn bulls_eyes(&self, y: i64, (left, top, right, bottom): (i64, i64, i64, i64)) -> Vec<(i64, i64)> {
+        // Width of the module `runs[idx]` would be, if it's the center of a bulls eye.
+        fn module_width(runs: &[(i64, i64, bool)], idx: usize) -> Option<i64> {
+            if idx < 4 || idx + 4 >= runs.len() || !runs[idx].2 {
+                return None;
+            }
+            let total: i64 = runs[idx - 3..=idx + 3].iter().map(|run| run.1).sum();
+            let even = runs[idx - 3..=idx + 3].iter().all(|run| run.1 * 14 >= total && run.1 * 14 <= total * 3);
+            // The outermost ring may run into dark modules beyond it.
+            let ring = runs[idx - 4].1 * 14 >= total && runs[idx + 4].1 * 14 >= total;
+            (even && ring).then_some(total / 7)
+        }
+
+        let row = self.runs((left, y), (1, 0), right - left + 1);
+        let mut found = vec![];
+        for idx in 0..row.len() {
+            let Some(width) = module_width(&row, idx) else {
+                continue;
+            };
+            let x = left + row[idx].0 + row[idx].1 / 2;
+            let column = self.runs((x, top), (0, 1), bottom - top + 1);
+            let Some(vertical) = column.iter().position(|run| top + run.0 <= y && y < top + run.0 + run.1) else {
+                continue;
+            };
+            if module_width(&column, vertical).is_some_and(|height| height * 2 >= width && height <= width * 2) {
+                found.push((x, top + column[vertical].0 + column[vertical].1 / 2));
+            }
+        }
+        found
+    }
+
+    /// Samples the `size` by `size` modules starting at `origin`, `pitch` pixels apart.
+    fn sample(&self, origin: (f64, f64), pitch: (f64, f64), size: usize) -> BitMatrix {
+        let mut matrix = BitMatrix::new(size, size);
+        for y in 0..size {
+            for x in 0..size {
+                let px = origin.0 + (x as f64 + 0.5) * pitch.0;
+                let py = origin.1 + (y as f64 + 0.5) * pitch.1;
+                if self.dark(px as i64, py as i64) {
+                    matrix.set(x, y);
+                }
+            }
+        }
+        matrix
+    }
+}
+
+/// Reads a QR code from the finder pattern in its top left corner, which is seven modules wide.
+fn read_qr(picture: &Picture, (left, top, right, bottom): (i64, i64, i64, i64)) -> Result<Vec<u8>, BarcodeError> {
+    let Some(finder) = picture.transition((left, top), (1, 0), 1) else {
+        return Err(BarcodeError::WrongFormat(BarcodeFormat::Qr));
+    };
+    let (width, height) = ((right - left + 1) as f64, (bottom - top + 1) as f64);
+    let size = (width * 7.0 / (finder - left) as f64).round() as usize;
+    // Versions 1 to 40 are 21 to 177 modules across in steps of four, anything else isn't a QR
+    // code and may be far too big to sample.
+    if !(21..=177).contains(&size) || !(size - 17).is_multiple_of(4) {
+        return Err(BarcodeError::WrongFormat(BarcodeFormat::Qr));
+    }
+    qr::decode(&picture.sample((left as f64, top as f64), (width / size as f64, height / size as f64), size))
+}
+
+/// Reads an Aztec code from its bulls eye. The rings around the center module give the pitch,
+/// and the mode message around them the symbol's size, as its edges may be light.
+fn read_aztec(picture: &Picture, bounds: (i64, i64, i64, i64)) -> Result<Vec<u8>, BarcodeError> {
+    let (_, top, _, bottom) = bounds;
+    let middle = (top + bottom) / 2;
+    // Rows nearest the middle first, where the bulls eye should be.
+    let rows = (0..=(bottom - top) / 2 + 1).flat_map(|offset| [middle - offset, middle + offset + 1]);
+    let mut tried = vec![];
+    let mut error = BarcodeError::WrongFormat(BarcodeFormat::Aztec);
+    for (x, y) in rows.filter(|y| (top..=bottom).contains(y)).flat_map(|y| picture.bulls_eyes(y, bounds)) {
+        // Every row through the center module finds it again.
+        if tried.contains(&(x, y)) {
+            continue;
+        }
+        tried.push((x, y));
+
+        // The fourth change of color out of the center module is the inside of the dark ring
+        // seven modules across.
+        let (Some(left), Some(right), Some(top), Some(bottom)) = (
+            picture.transition((x, y), (-1, 0), 4),
+            picture.transition((x, y), (1, 0), 4),
+            picture.transition((x, y), (0, -1), 4),
+            picture.transition((x, y), (0, 1), 4),
+        ) else {
+            continue;
+        };
+        let pitch = ((right - left - 1) as f64 / 7.0, (bottom - top - 1) as f64 / 7.0);
+        let center = ((left + 1 + right) as f64 / 2.0, (top + 1 + bottom) as f64 / 2.0);
+        let sample = |size: usize| {
+            let origin = (center.0 - size as f64 / 2.0 * pitch.0, center.1 - size as f64 / 2.0 * pitch.1);
+            picture.sample(origin, pitch, size)
+        };
+
+        match aztec::size(&sample(aztec::CORE_SIZE)).and_then(|size| aztec::decode(&sample(size))) {
+            Ok(data) => return Ok(data),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+/// Reads the QR or Aztec code in `image`, which should be upright and unskewed, as screenshots
+/// and renders are. Returns the symbology and the raw message bytes.
+pub fn read(image: &GrayImage) -> Result<(BarcodeFormat, Vec<u8>), BarcodeError> {
+    let (min, max) = image.pixels().fold((u8::MAX, u8::MIN), |(min, max), p| (min.min(p.0[0]), max.max(p.0[0])));
+    if max - min < 64 {
+        return Err(BarcodeError::NoBarcode);
+    }
+    let picture = Picture { image, threshold: min + (max - min) / 2 };
+
+    let mut bounds = (i64::MAX, i64::MAX, i64::MIN, i64::MIN);
+    for (x, y, _) in image.enumerate_pixels() {
+        if picture.dark(x as i64, y as i64) {
+            bounds = (bounds.0.min(x as i64), bounds.1.min(y as i64), bounds.2.max(x as i64), bounds.3.max(y as i64));
+        }
+    }
+
+    match read_qr(&picture, bounds) {
+        Ok(data) => Ok((BarcodeFormat::Qr, data)),
+        Err(qr_error) => match read_aztec(&picture, bounds) {
+            Ok(data) => Ok((BarcodeFormat::Aztec, data)),
+            Err(aztec_error) => Err(BarcodeError::Unreadable {
+                qr: Box::new(qr_error),
+                aztec: Box::new(aztec_error),
+            }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARDING_PASS: &str = "M1DESMARAIS/LUC       EABC123 YULFRAAC 0834 226F001A0025 100";
+
+    /// `len` characters cycling through a boarding pass message, which mixes modes and needs
+    /// binary shifts in Aztec codes.
+    fn message(len: usize) -> String {
+        BOARDING_PASS.chars().chain(" ^abc-XYZ 0123456789".chars()).cycle().take(len).collect()
+    }
+
+    fn round_trip(format: BarcodeFormat, message: &str, options: &RenderOptions) -> Result<(), String> {
+        let image = render(format, message, "iso-8859-1", options).map_err(|e| e.to_string())?;
+        match read(&image) {
+            Ok((found, data)) if found == format && data == message.as_bytes() => Ok(()),
+            Ok((found, data)) => Err(format!("read {found:?} {:?}", String::from_utf8_lossy(&data))),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn check_lengths(format: BarcodeFormat) {
+        let mut failures = vec![];
+        for module_size in [1, 3, 4, 5, 8, 10] {
+            let options = RenderOptions { module_size, ..RenderOptions::default() };
+            // Every length at a few sizes, and enough of them at the rest to cover each symbol size.
+            let step = if module_size <= 3 { 1 } else { 7 };
+            for len in (1..200).step_by(step).chain([BOARDING_PASS.len()]) {
+                if let Err(e) = round_trip(format, &message(len), &options) {
+                    failures.push(format!("{len} characters at {module_size}px: {e}"));
+                }
+            }
+        }
+        assert!(failures.is_empty(), "{failures:#?}");
+    }
+
+    #[test]
+    fn qr_round_trips() {
+        check_lengths(BarcodeFormat::Qr);
+    }
+
+    #[test]
+    fn aztec_round_trips() {
+        check_lengths(BarcodeFormat::Aztec);
+    }
+
+    #[test]
+    fn round_trips_at_every_error_correction_level() {
+        for error_correction in [ErrorCorrection::Low, ErrorCorrection::Medium, ErrorCorrection::Quartile, ErrorCorrection::High] {
+            let options = RenderOptions { error_correction, ..RenderOptions::default() };
+            for format in [BarcodeFormat::Qr, BarcodeFormat::Aztec] {
+                assert_eq!(round_trip(format, BOARDING_PASS, &options), Ok(()), "{format:?} at {error_correction:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn wide_pictures_are_not_sampled_as_qr_codes() {
+        // A one pixel finder across 4000 pixels would make a QR code 28000 modules across.
+        let mut image = GrayImage::from_pixel(4000, 20, image::Luma([255]));
+        image.put_pixel(0, 0, image::Luma([0]));
+        image.put_pixel(3999, 19, image::Luma([0]));
+        let picture = Picture { image: &image, threshold: 128 };
+        assert!(matches!(read_qr(&picture, (0, 0, 3999, 19)), Err(BarcodeError::WrongFormat(BarcodeFormat::Qr))));
+    }
+
+    #[test]
+    fn reads_a_quiet_zone_free_aztec_code() {
+        let options = RenderOptions { quiet_zone: Some(0), ..RenderOptions::default() };
+        assert_eq!(round_trip(BarcodeFormat::Aztec, BOARDING_PASS, &options), Ok(()));
+    }
+}
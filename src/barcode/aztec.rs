@@ -0,0 +1,641 @@
+//! Aztec code, laid out the way ISO/IEC 24778 describes it: a compact symbol up to four layers,
+//! a full range symbol beyond that.
+
+use super::{BarcodeError, BitMatrix};
+use crate::pass::BarcodeFormat;
+
+/// Bits, most significant first.
+#[derive(Debug, Clone, Default)]
+struct Bits(Vec<bool>);
+
+impl Bits {
+    fn push(&mut self, value: u32, count: usize) {
+        for shift in (0..count).rev() {
+            self.0.push(value >> shift & 1 == 1);
+        }
+    }
+
+    fn get(&self, idx: usize) -> bool {
+        self.0.get(idx).copied().unwrap_or(false)
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Upper,
+    Lower,
+    Digit,
+}
+
+impl Mode {
+    fn bits(self) -> usize {
+        match self {
+            Self::Upper | Self::Lower => 5,
+            Self::Digit => 4,
+        }
+    }
+
+    fn code(self, byte: u8) -> Option<u32> {
+        match (self, byte) {
+            (_, b' ') => Some(1),
+            (Self::Upper, b'A'..=b'Z') => Some((byte - b'A') as u32 + 2),
+            (Self::Lower, b'a'..=b'z') => Some((byte - b'a') as u32 + 2),
+            (Self::Digit, b'0'..=b'9') => Some((byte - b'0') as u32 + 2),
+            (Self::Digit, b',') => Some(12),
+            (Self::Digit, b'.') => Some(13),
+            _ => None,
+        }
+    }
+}
+
+// Latch and shift codes, named after the mode they are sent from.
+const UPPER_LL: u32 = 28;
+const UPPER_DL: u32 = 30;
+const LOWER_US: u32 = 28;
+const LOWER_DL: u32 = 30;
+const DIGIT_UL: u32 = 14;
+const DIGIT_US: u32 = 15;
+const BINARY_SHIFT: u32 = 31;
+
+/// Longest run a single binary shift can hold.
+const MAX_BINARY_SHIFT: usize = 2047 + 31;
+
+/// Encodes `data` with the upper, lower and digit modes, binary shifting whatever they can't
+/// hold.
+fn high_level_encode(data: &[u8]) -> Bits {
+    let mut bits = Bits::default();
+    let mut mode = Mode::Upper;
+    let encodable = |b: u8| [Mode::Upper, Mode::Lower, Mode::Digit].iter().any(|m| m.code(b).is_some());
+
+    let mut idx = 0;
+    while idx < data.len() {
+        let byte = data[idx];
+        if let Some(code) = mode.code(byte) {
+            bits.push(code, mode.bits());
+            idx += 1;
+            continue;
+        }
+
+        if let Some(code) = Mode::Upper.code(byte) {
+            // Shift for a single capital, latch for a run of them.
+            let run = data.get(idx + 1).is_some_and(|&b| Mode::Upper.code(b).is_some());
+            match (mode, run) {
+                (Mode::Lower, false) => {
+                    bits.push(LOWER_US, 5);
+                    bits.push(code, 5);
+                    idx += 1;
+                },
+                (Mode::Digit, false) => {
+                    bits.push(DIGIT_US, 4);
+                    bits.push(code, 5);
+                    idx += 1;
+                },
+                (Mode::Lower, true) => {
+                    bits.push(LOWER_DL, 5);
+                    bits.push(DIGIT_UL, 4);
+                    mode = Mode::Upper;
+                },
+                (Mode::Digit, true) => {
+                    bits.push(DIGIT_UL, 4);
+                    mode = Mode::Upper;
+                },
+                (Mode::Upper, _) => unreachable!("upper mode holds every capital"),
+            }
+            continue;
+        }
+
+        if Mode::Lower.code(byte).is_some() {
+            if mode == Mode::Digit {
+                bits.push(DIGIT_UL, 4);
+            }
+            bits.push(UPPER_LL, 5);
+            mode = Mode::Lower;
+            continue;
+        }
+
+        if Mode::Digit.code(byte).is_some() {
+            bits.push(if mode == Mode::Upper { UPPER_DL } else { LOWER_DL }, 5);
+            mode = Mode::Digit;
+            continue;
+        }
+
+        // Digit mode has no binary shift of its own.
+        if mode == Mode::Digit {
+            bits.push(DIGIT_UL, 4);
+            mode = Mode::Upper;
+        }
+        let run = data[idx..].iter().take_while(|&&b| !encodable(b)).count().min(MAX_BINARY_SHIFT);
+        bits.push(BINARY_SHIFT, 5);
+        if run <= 31 {
+            bits.push(run as u32, 5);
+        } else {
+            bits.push(0, 5);
+            bits.push((run - 31) as u32, 11);
+        }
+        for &b in &data[idx..idx + run] {
+            bits.push(b as u32, 8);
+        }
+        idx += run;
+    }
+
+    bits
+}
+
+/// Galois field GF(2^m) for the Reed-Solomon check words.
+struct GaloisField {
+    exp: Vec<u32>,
+    log: Vec<u32>,
+}
+
+impl GaloisField {
+    fn new(primitive: u32, size: usize) -> Self {
+        let mut exp = vec![0; size];
+        let mut log = vec![0; size];
+        let mut x = 1;
+        for value in exp.iter_mut() {
+            *value = x;
+            x <<= 1;
+            if x as usize >= size {
+                x ^= primitive;
+            }
+        }
+        for idx in 0..size - 1 {
+            log[exp[idx] as usize] = idx as u32;
+        }
+        Self {
+            exp,
+            log,
+        }
+    }
+
+    fn for_word_size(word_size: usize) -> Self {
+        match word_size {
+            4 => Self::new(0x13, 16),
+            6 => Self::new(0x43, 64),
+            8 => Self::new(0x12d, 256),
+            10 => Self::new(0x409, 1024),
+            12 => Self::new(0x1069, 4096),
+            _ => unreachable!("Aztec words are 4 to 12 bits"),
+        }
+    }
+
+    fn mul(&self, a: u32, b: u32) -> u32 {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+        let order = self.exp.len() - 1;
+        self.exp[(self.log[a as usize] as usize + self.log[b as usize] as usize) % order]
+    }
+
+    /// Check words for `data`, from the generator with roots α^1 to α^`count`.
+    fn check_words(&self, data: &[u32], count: usize) -> Vec<u32> {
+        let mut generator = vec![1];
+        for power in 1..=count {
+            let root = self.exp[power % (self.exp.len() - 1)];
+            let mut next = vec![0; generator.len() + 1];
+            for (idx, &coefficient) in generator.iter().enumerate() {
+                next[idx] ^= coefficient;
+                next[idx + 1] ^= self.mul(coefficient, root);
+            }
+            generator = next;
+        }
+
+        let mut remainder = vec![0; count];
+        for &word in data {
+            let factor = word ^ remainder[0];
+            remainder.remove(0);
+            remainder.push(0);
+            for (value, &coefficient) in remainder.iter_mut().zip(&generator[1..]) {
+                *value ^= self.mul(coefficient, factor);
+            }
+        }
+        remainder
+    }
+
+    /// Whether `words`, check words included, have no errors.
+    fn is_valid(&self, words: &[u32], check_count: usize) -> bool {
+        (1..=check_count).all(|power| {
+            let root = self.exp[power % (self.exp.len() - 1)];
+            words.iter().fold(0, |acc, &word| self.mul(acc, root) ^ word) == 0
+        })
+    }
+}
+
+/// Appends check words to `bits` so the result fills `total_bits`, padding at the front.
+fn with_check_words(bits: &Bits, total_bits: usize, word_size: usize) -> Bits {
+    let field = GaloisField::for_word_size(word_size);
+    let data: Vec<u32> = bits.0.chunks(word_size)
+        .map(|word| word.iter().fold(0, |acc, &bit| acc << 1 | bit as u32))
+        .collect();
+    let total_words = total_bits / word_size;
+    let check_words = field.check_words(&data, total_words - data.len());
+
+    let mut out = Bits::default();
+    out.push(0, total_bits % word_size);
+    for word in data.into_iter().chain(check_words) {
+        out.push(word, word_size);
+    }
+    out
+}
+
+/// Splits `bits` into words, so no word is all zeros or all ones. Either would be
+/// mistaken for an erasure.
+fn stuff_bits(bits: &Bits, word_size: usize) -> Bits {
+    let mut out = Bits::default();
+    let mask = (1 << word_size) - 2;
+    let mut idx = 0;
+    while idx < bits.len() {
+        let mut word = 0;
+        for offset in 0..word_size {
+            // The last word is padded with ones.
+            if idx + offset >= bits.len() || bits.get(idx + offset) {
+                word |= 1 << (word_size - 1 - offset);
+            }
+        }
+        if word & mask == mask {
+            out.push(word & mask, word_size);
+            idx += word_size - 1;
+        } else if word & mask == 0 {
+            out.push(word | 1, word_size);
+            idx += word_size - 1;
+        } else {
+            out.push(word, word_size);
+            idx += word_size;
+        }
+    }
+    out
+}
+
+fn total_bits_in_layers(layers: usize, compact: bool) -> usize {
+    ((if compact { 88 } else { 112 }) + 16 * layers) * layers
+}
+
+fn word_size(layers: usize) -> usize {
+    match layers {
+        0 => 4,
+        1..=2 => 6,
+        3..=8 => 8,
+        9..=22 => 10,
+        _ => 12,
+    }
+}
+
+fn draw_bulls_eye(matrix: &mut BitMatrix, center: usize, size: usize) {
+    for ring in (0..size).step_by(2) {
+        for idx in center - ring..=center + ring {
+            matrix.set(idx, center - ring);
+            matrix.set(idx, center + ring);
+            matrix.set(center - ring, idx);
+            matrix.set(center + ring, idx);
+        }
+    }
+    // Orientation marks.
+    matrix.set(center - size, center - size);
+    matrix.set(center - size + 1, center - size);
+    matrix.set(center - size, center - size + 1);
+    matrix.set(center + size, center - size);
+    matrix.set(center + size, center - size + 1);
+    matrix.set(center + size, center + size - 1);
+}
+
+/// Where each bit of the mode message goes, around the bulls eye.
+fn mode_message_positions(compact: bool, size: usize) -> Vec<(usize, usize)> {
+    let center = size / 2;
+    if compact {
+        let mut positions = vec![(0, 0); 28];
+        for idx in 0..7 {
+            let offset = center - 3 + idx;
+            positions[idx] = (offset, center - 5);
+            positions[idx + 7] = (center + 5, offset);
+            positions[20 - idx] = (offset, center + 5);
+            positions[27 - idx] = (center - 5, offset);
+        }
+        positions
+    } else {
+        let mut positions = vec![(0, 0); 40];
+        for idx in 0..10 {
+            // Skip the reference grid line through the center.
+            let offset = center - 5 + idx + idx / 5;
+            positions[idx] = (offset, center - 7);
+            positions[idx + 10] = (center + 7, offset);
+            positions[29 - idx] = (offset, center + 7);
+            positions[39 - idx] = (center - 7, offset);
+        }
+        positions
+    }
+}
+
+/// Size of a symbol, and where each bit of its data layers goes.
+fn data_positions(compact: bool, layers: usize) -> (usize, Vec<(usize, usize)>) {
+    // Full range symbols have a reference grid every 16 modules, which the data flows around.
+    let base_size = (if compact { 11 } else { 14 }) + layers * 4;
+    let mut alignment = vec![0; base_size];
+    let size = if compact {
+        for (idx, value) in alignment.iter_mut().enumerate() {
+            *value = idx;
+        }
+        base_size
+    } else {
+        let size = base_size + 1 + 2 * ((base_size / 2 - 1) / 15);
+        let original_center = base_size / 2;
+        let center = size / 2;
+        for idx in 0..original_center {
+            let offset = idx + idx / 15;
+            alignment[original_center - idx - 1] = center - offset - 1;
+            alignment[original_center + idx] = center + offset + 1;
+        }
+        size
+    };
+
+    // Layers spiral inwards, two modules thick, each side written in turn.
+    let mut positions = vec![(0, 0); total_bits_in_layers(layers, compact)];
+    let mut row_offset = 0;
+    for layer in 0..layers {
+        let row_size = (layers - layer) * 4 + if compact { 9 } else { 12 };
+        for j in 0..row_size {
+            let column_offset = j * 2;
+            for k in 0..2 {
+                positions[row_offset + column_offset + k] = (alignment[layer * 2 + k], alignment[layer * 2 + j]);
+                positions[row_offset + row_size * 2 + column_offset + k] = (alignment[layer * 2 + j], alignment[base_size - 1 - layer * 2 - k]);
+                positions[row_offset + row_size * 4 + column_offset + k] = (alignment[base_size - 1 - layer * 2 - k], alignment[base_size - 1 - layer * 2 - j]);
+                positions[row_offset + row_size * 6 + column_offset + k] = (alignment[base_size - 1 - layer * 2 - j], alignment[layer * 2 + k]);
+            }
+        }
+        row_offset += row_size * 8;
+    }
+
+    (size, positions)
+}
+
+/// Encodes `data`, reserving `ec_percent` percent of the symbol for error correction.
+pub(super) fn encode(data: &[u8], ec_percent: usize) -> Result<BitMatrix, BarcodeError> {
+    if data.is_empty() {
+        return Err(BarcodeError::Empty(BarcodeFormat::Aztec));
+    }
+    let bits = high_level_encode(data);
+    let ec_bits = bits.len() * ec_percent / 100 + 11;
+    let total_size_bits = bits.len() + ec_bits;
+
+    // Compact symbols cover one to four layers, full range symbols four to 32.
+    let mut chosen = None;
+    for idx in 0..=32 {
+        let compact = idx <= 3;
+        let layers = if compact { idx + 1 } else { idx };
+        let total_bits = total_bits_in_layers(layers, compact);
+        if total_size_bits > total_bits {
+            continue;
+        }
+        let word_size = word_size(layers);
+        let usable_bits = total_bits - total_bits % word_size;
+        let stuffed = stuff_bits(&bits, word_size);
+        if stuffed.len() + ec_bits > usable_bits {
+            continue;
+        }
+        // The compact mode message only has room for 64 data words.
+        if compact && stuffed.len() > word_size * 64 {
+            continue;
+        }
+        chosen = Some((compact, layers, total_bits, word_size, stuffed));
+        break;
+    }
+    let Some((compact, layers, total_bits, word_size, stuffed)) = chosen else {
+        return Err(BarcodeError::TooLong(BarcodeFormat::Aztec));
+    };
+
+    let message_words = stuffed.len() / word_size;
+    let message_bits = with_check_words(&stuffed, total_bits, word_size);
+
+    let mut mode_message = Bits::default();
+    let mode_message = if compact {
+        mode_message.push(layers as u32 - 1, 2);
+        mode_message.push(message_words as u32 - 1, 6);
+        with_check_words(&mode_message, 28, 4)
+    } else {
+        mode_message.push(layers as u32 - 1, 5);
+        mode_message.push(message_words as u32 - 1, 11);
+        with_check_words(&mode_message, 40, 4)
+    };
+
+    let (size, positions) = data_positions(compact, layers);
+    let mut matrix = BitMatrix::new(size, size);
+    for (idx, &(x, y)) in positions.iter().enumerate() {
+        if message_bits.get(idx) {
+            matrix.set(x, y);
+        }
+    }
+    for (idx, &(x, y)) in mode_message_positions(compact, size).iter().enumerate() {
+        if mode_message.get(idx) {
+            matrix.set(x, y);
+        }
+    }
+
+    if compact {
+        draw_bulls_eye(&mut matrix, size / 2, 5);
+    } else {
+        draw_bulls_eye(&mut matrix, size / 2, 7);
+        let base_size = 14 + layers * 4;
+        let center = size / 2;
+        let mut j = 0;
+        for _ in (0..base_size / 2 - 1).step_by(15) {
+            for k in ((center & 1)..size).step_by(2) {
+                matrix.set(center - j, k);
+                matrix.set(center + j, k);
+                matrix.set(k, center - j);
+                matrix.set(k, center + j);
+            }
+            j += 16;
+        }
+    }
+
+    Ok(matrix)
+}
+
+/// Code tables of the high level encoding, decoding being the only thing that needs all five.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Table {
+    Upper,
+    Lower,
+    Mixed,
+    Punct,
+    Digit,
+}
+
+/// Mixed mode codes 1 to 27: space, control characters and a few symbols.
+const MIXED: [u8; 27] = [
+    b' ', 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 27, 28, 29, 30, 31,
+    b'@', b'\\', b'^', b'_', b'`', b'|', b'~', 127,
+];
+
+/// Punctuation mode codes 1 to 30.
+const PUNCT: [&[u8]; 30] = [
+    b"\r", b"\r\n", b". ", b", ", b": ", b"!", b"\"", b"#", b"$", b"%", b"&", b"'", b"(", b")", b"*",
+    b"+", b",", b"-", b".", b"/", b":", b";", b"<", b"=", b">", b"?", b"[", b"]", b"{", b"}",
+];
+
+/// Reads bits, most significant first.
+struct BitReader<'a> {
+    bits: &'a [bool],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: usize) -> Option<u32> {
+        let bits = self.bits.get(self.pos..self.pos + count)?;
+        self.pos += count;
+        Some(bits.iter().fold(0, |acc, &bit| acc << 1 | bit as u32))
+    }
+}
+
+/// Decodes the high level encoding, whichever tables the encoder chose.
+fn high_level_decode(bits: &[bool]) -> Result<Vec<u8>, BarcodeError> {
+    let invalid = |message: String| BarcodeError::InvalidData {
+        format: BarcodeFormat::Aztec,
+        message,
+    };
+    let mut reader = BitReader { bits, pos: 0 };
+    let mut out = vec![];
+    let mut latch = Table::Upper;
+    let mut shift = None;
+    loop {
+        let table = shift.take().unwrap_or(latch);
+        let start = reader.pos;
+        // Whatever is left is padding once a whole code no longer fits.
+        let Some(code) = reader.read(if table == Table::Digit { 4 } else { 5 }) else {
+            break;
+        };
+        match (table, code) {
+            (Table::Digit, 0) | (Table::Upper | Table::Lower | Table::Mixed, 0) => shift = Some(Table::Punct),
+            (Table::Digit, 1) => out.push(b' '),
+            (Table::Digit, 2..=11) => out.push(b'0' + code as u8 - 2),
+            (Table::Digit, 12) => out.push(b','),
+            (Table::Digit, 13) => out.push(b'.'),
+            (Table::Digit, 14) | (Table::Mixed, 29) | (Table::Punct, 31) => latch = Table::Upper,
+            (Table::Digit, 15) | (Table::Lower, 28) => shift = Some(Table::Upper),
+            (Table::Upper | Table::Lower | Table::Mixed, 31) => {
+                let len = match reader.read(5) {
+                    Some(0) => reader.read(11).map(|len| len as usize + 31),
+                    len => len.map(|len| len as usize),
+                };
+                let bytes: Option<Vec<u8>> = len.and_then(|len| (0..len).map(|_| reader.read(8).map(|b| b as u8)).collect());
+                match bytes {
+                    Some(bytes) => out.extend(bytes),
+                    // The last word is padded with ones, which read as a binary shift that runs out.
+                    None if bits[start..].iter().all(|&bit| bit) => break,
+                    None => return Err(invalid("binary shift runs past the data".to_owned())),
+                }
+            },
+            (Table::Upper | Table::Lower, 1) => out.push(b' '),
+            (Table::Upper, 2..=27) => out.push(b'A' + code as u8 - 2),
+            (Table::Lower, 2..=27) => out.push(b'a' + code as u8 - 2),
+            (Table::Upper | Table::Mixed, 28) => latch = Table::Lower,
+            (Table::Upper | Table::Lower, 29) => latch = Table::Mixed,
+            (Table::Upper | Table::Lower, 30) => latch = Table::Digit,
+            (Table::Mixed, 1..=27) => out.push(MIXED[code as usize - 1]),
+            (Table::Mixed, 30) => latch = Table::Punct,
+            // FNC1 or an ECI designator, which don't change the bytes.
+            (Table::Punct, 0) => {
+                let digits = reader.read(3).unwrap_or(0);
+                if digits == 7 {
+                    return Err(invalid("invalid FLG(7)".to_owned()));
+                }
+                reader.read(digits as usize * 4);
+            },
+            (Table::Punct, 1..=30) => out.extend_from_slice(PUNCT[code as usize - 1]),
+            _ => return Err(invalid(format!("invalid code {code} in {table:?} mode"))),
+        }
+    }
+
+    Ok(out)
+}
+
+fn read_words(matrix: &BitMatrix, positions: &[(usize, usize)], word_size: usize) -> Vec<u32> {
+    let skip = positions.len() % word_size;
+    positions[skip..].chunks(word_size)
+        .map(|word| word.iter().fold(0, |acc, &(x, y)| acc << 1 | matrix.get(x, y) as u32))
+        .collect()
+}
+
+/// Whether the symbol centered in `matrix` is compact, and its layers and data words, from its
+/// bulls eye and mode message. `matrix` may be a crop around the bulls eye.
+fn read_mode_message(matrix: &BitMatrix) -> Result<(bool, usize, usize), BarcodeError> {
+    let size = matrix.width();
+    if matrix.height() != size || size < 15 || size.is_multiple_of(2) {
+        return Err(BarcodeError::WrongFormat(BarcodeFormat::Aztec));
+    }
+    let center = size / 2;
+    let ring = |distance: usize, dark: bool| (center - distance..=center + distance).all(|idx| {
+        [(idx, center - distance), (idx, center + distance), (center - distance, idx), (center + distance, idx)]
+            .iter()
+            .all(|&(x, y)| matrix.get(x, y) == dark)
+    });
+    if !(ring(0, true) && ring(1, false) && ring(2, true) && ring(3, false) && ring(4, true)) {
+        return Err(BarcodeError::WrongFormat(BarcodeFormat::Aztec));
+    }
+    // Full range bulls eyes have another ring where compact symbols have their mode message.
+    let compact = !(ring(5, false) && ring(6, true));
+
+    let mode_message = read_words(matrix, &mode_message_positions(compact, size), 4);
+    let data_words = if compact { 2 } else { 4 };
+    if !GaloisField::for_word_size(4).is_valid(&mode_message, mode_message.len() - data_words) {
+        return Err(BarcodeError::Damaged(BarcodeFormat::Aztec));
+    }
+    let (layers, message_words) = if compact {
+        ((mode_message[0] >> 2) as usize + 1, ((mode_message[0] & 3) << 4 | mode_message[1]) as usize + 1)
+    } else {
+        (
+            (mode_message[0] << 1 | mode_message[1] >> 3) as usize + 1,
+            ((mode_message[1] & 7) << 8 | mode_message[2] << 4 | mode_message[3]) as usize + 1,
+        )
+    };
+
+    Ok((compact, layers, message_words))
+}
+
+/// Modules across the bulls eye and mode message of a full range symbol, the larger kind.
+pub(super) const CORE_SIZE: usize = 15;
+
+/// Modules across the symbol whose bulls eye is centered in `core`, from its mode message. A
+/// core of [`CORE_SIZE`] modules holds the mode message of either kind of symbol.
+pub(super) fn size(core: &BitMatrix) -> Result<usize, BarcodeError> {
+    let (compact, layers, _) = read_mode_message(core)?;
+    Ok(data_positions(compact, layers).0)
+}
+
+/// Decodes an upright symbol. Damaged symbols are rejected rather than corrected.
+pub(super) fn decode(matrix: &BitMatrix) -> Result<Vec<u8>, BarcodeError> {
+    let size = matrix.width();
+    let (compact, layers, message_words) = read_mode_message(matrix)?;
+    let (expected_size, positions) = data_positions(compact, layers);
+    if expected_size != size {
+        return Err(BarcodeError::InvalidData {
+            format: BarcodeFormat::Aztec,
+            message: format!("{layers} layers don't fit a symbol {size} modules across"),
+        });
+    }
+    let word_size = word_size(layers);
+    let words = read_words(matrix, &positions, word_size);
+    if message_words > words.len() {
+        return Err(BarcodeError::InvalidData {
+            format: BarcodeFormat::Aztec,
+            message: format!("mode message has {message_words} data words, more than the symbol holds"),
+        });
+    }
+    if !GaloisField::for_word_size(word_size).is_valid(&words, words.len() - message_words) {
+        return Err(BarcodeError::Damaged(BarcodeFormat::Aztec));
+    }
+
+    // Undo the bit stuffing, see `stuff_bits`.
+    let mask = (1 << word_size) - 2;
+    let mut bits = vec![];
+    for &word in &words[..message_words] {
+        let stuffed = word & mask == mask || word & mask == 0;
+        let len = if stuffed { word_size - 1 } else { word_size };
+        bits.extend((0..len).map(|idx| word >> (word_size - 1 - idx) & 1 == 1));
+    }
+
+    high_level_decode(&bits)
+}
@@ -0,0 +1,141 @@
+//! Code 128. Runs of digits use code set C, everything else A or B, with FNC4 for bytes above
+//! 127.
+
+use super::BitMatrix;
+
+/// Bar and space widths of every symbol value, bar first.
+const PATTERNS: [[u8; 6]; 106] = [
+    [2, 1, 2, 2, 2, 2], [2, 2, 2, 1, 2, 2], [2, 2, 2, 2, 2, 1], [1, 2, 1, 2, 2, 3], [1, 2, 1, 3, 2, 2], [1, 3, 1, 2, 2, 2],
+    [1, 2, 2, 2, 1, 3], [1, 2, 2, 3, 1, 2], [1, 3, 2, 2, 1, 2], [2, 2, 1, 2, 1, 3], [2, 2, 1, 3, 1, 2], [2, 3, 1, 2, 1, 2],
+    [1, 1, 2, 2, 3, 2], [1, 2, 2, 1, 3, 2], [1, 2, 2, 2, 3, 1], [1, 1, 3, 2, 2, 2], [1, 2, 3, 1, 2, 2], [1, 2, 3, 2, 2, 1],
+    [2, 2, 3, 2, 1, 1], [2, 2, 1, 1, 3, 2], [2, 2, 1, 2, 3, 1], [2, 1, 3, 2, 1, 2], [2, 2, 3, 1, 1, 2], [3, 1, 2, 1, 3, 1],
+    [3, 1, 1, 2, 2, 2], [3, 2, 1, 1, 2, 2], [3, 2, 1, 2, 2, 1], [3, 1, 2, 2, 1, 2], [3, 2, 2, 1, 1, 2], [3, 2, 2, 2, 1, 1],
+    [2, 1, 2, 1, 2, 3], [2, 1, 2, 3, 2, 1], [2, 3, 2, 1, 2, 1], [1, 1, 1, 3, 2, 3], [1, 3, 1, 1, 2, 3], [1, 3, 1, 3, 2, 1],
+    [1, 1, 2, 3, 1, 3], [1, 3, 2, 1, 1, 3], [1, 3, 2, 3, 1, 1], [2, 1, 1, 3, 1, 3], [2, 3, 1, 1, 1, 3], [2, 3, 1, 3, 1, 1],
+    [1, 1, 2, 1, 3, 3], [1, 1, 2, 3, 3, 1], [1, 3, 2, 1, 3, 1], [1, 1, 3, 1, 2, 3], [1, 1, 3, 3, 2, 1], [1, 3, 3, 1, 2, 1],
+    [3, 1, 3, 1, 2, 1], [2, 1, 1, 3, 3, 1], [2, 3, 1, 1, 3, 1], [2, 1, 3, 1, 1, 3], [2, 1, 3, 3, 1, 1], [2, 1, 3, 1, 3, 1],
+    [3, 1, 1, 1, 2, 3], [3, 1, 1, 3, 2, 1], [3, 3, 1, 1, 2, 1], [3, 1, 2, 1, 1, 3], [3, 1, 2, 3, 1, 1], [3, 3, 2, 1, 1, 1],
+    [3, 1, 4, 1, 1, 1], [2, 2, 1, 4, 1, 1], [4, 3, 1, 1, 1, 1], [1, 1, 1, 2, 2, 4], [1, 1, 1, 4, 2, 2], [1, 2, 1, 1, 2, 4],
+    [1, 2, 1, 4, 2, 1], [1, 4, 1, 1, 2, 2], [1, 4, 1, 2, 2, 1], [1, 1, 2, 2, 1, 4], [1, 1, 2, 4, 1, 2], [1, 2, 2, 1, 1, 4],
+    [1, 2, 2, 4, 1, 1], [1, 4, 2, 1, 1, 2], [1, 4, 2, 2, 1, 1], [2, 4, 1, 2, 1, 1], [2, 2, 1, 1, 1, 4], [4, 1, 3, 1, 1, 1],
+    [2, 4, 1, 1, 1, 2], [1, 3, 4, 1, 1, 1], [1, 1, 1, 2, 4, 2], [1, 2, 1, 1, 4, 2], [1, 2, 1, 2, 4, 1], [1, 1, 4, 2, 1, 2],
+    [1, 2, 4, 1, 1, 2], [1, 2, 4, 2, 1, 1], [4, 1, 1, 2, 1, 2], [4, 2, 1, 1, 1, 2], [4, 2, 1, 2, 1, 1], [2, 1, 2, 1, 4, 1],
+    [2, 1, 4, 1, 2, 1], [4, 1, 2, 1, 2, 1], [1, 1, 1, 1, 4, 3], [1, 1, 1, 3, 4, 1], [1, 3, 1, 1, 4, 1], [1, 1, 4, 1, 1, 3],
+    [1, 1, 4, 3, 1, 1], [4, 1, 1, 1, 1, 3], [4, 1, 1, 3, 1, 1], [1, 1, 3, 1, 4, 1], [1, 1, 4, 1, 3, 1], [3, 1, 1, 1, 4, 1],
+    [4, 1, 1, 1, 3, 1], [2, 1, 1, 4, 1, 2], [2, 1, 1, 2, 1, 4], [2, 1, 1, 2, 3, 2],
+];
+const STOP: [u8; 7] = [2, 3, 3, 1, 1, 1, 2];
+
+const START_A: u8 = 103;
+const START_B: u8 = 104;
+const START_C: u8 = 105;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+impl CodeSet {
+    fn start(self) -> u8 {
+        match self {
+            Self::A => START_A,
+            Self::B => START_B,
+            Self::C => START_C,
+        }
+    }
+
+    /// Value that switches from `self` to `to`.
+    fn switch_to(self, to: Self) -> u8 {
+        match (self, to) {
+            (Self::A, Self::B) | (Self::C, Self::B) => 100,
+            (Self::B, Self::A) | (Self::C, Self::A) => 101,
+            (Self::A, Self::C) | (Self::B, Self::C) => 99,
+            _ => unreachable!("switching to the current code set"),
+        }
+    }
+
+    fn fnc4(self) -> u8 {
+        match self {
+            Self::A => 101,
+            Self::B => 100,
+            Self::C => unreachable!("code set C has no FNC4"),
+        }
+    }
+}
+
+/// Symbol values, start code first, without the check symbol.
+fn symbol_values(data: &[u8]) -> Vec<u8> {
+    fn switch(values: &mut Vec<u8>, set: &mut Option<CodeSet>, to: CodeSet) {
+        match *set {
+            None => values.push(to.start()),
+            Some(from) if from != to => values.push(from.switch_to(to)),
+            Some(_) => {},
+        }
+        *set = Some(to);
+    }
+
+    let mut values = vec![];
+    let mut set: Option<CodeSet> = None;
+
+    let mut idx = 0;
+    while idx < data.len() {
+        let digits = data[idx..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+        if set == Some(CodeSet::C) {
+            if digits >= 2 {
+                values.push((data[idx] - b'0') * 10 + (data[idx + 1] - b'0'));
+                idx += 2;
+                continue;
+            }
+        } else if digits >= 4 && digits % 2 == 0 || digits >= 2 && digits == data.len() - idx && digits % 2 == 0 {
+            // Pairs of digits take half the symbols in code set C.
+            switch(&mut values, &mut set, CodeSet::C);
+            continue;
+        }
+
+        let byte = data[idx];
+        let extended = byte >= 128;
+        let c = byte & 0x7f;
+        let wanted = match set {
+            _ if c < 32 => CodeSet::A,
+            _ if c >= 96 => CodeSet::B,
+            Some(CodeSet::A) => CodeSet::A,
+            _ => CodeSet::B,
+        };
+        switch(&mut values, &mut set, wanted);
+        if extended {
+            values.push(wanted.fnc4());
+        }
+        values.push(match wanted {
+            CodeSet::A if c < 32 => c + 64,
+            _ => c - 32,
+        });
+        idx += 1;
+    }
+
+    if values.is_empty() {
+        values.push(START_B);
+    }
+    values
+}
+
+pub(super) fn encode(data: &[u8], bar_height: u32) -> BitMatrix {
+    let mut values = symbol_values(data);
+    let checksum = values.iter()
+        .enumerate()
+        .map(|(idx, &value)| idx.max(1) as u32 * value as u32)
+        .sum::<u32>() % 103;
+    values.push(checksum as u8);
+
+    let mut row = vec![];
+    let widths = values.iter().flat_map(|&v| PATTERNS[v as usize].iter()).chain(STOP.iter());
+    for (idx, &width) in widths.enumerate() {
+        // Widths alternate bar, space, starting with a bar.
+        let dark = idx % 2 == 0;
+        row.extend(std::iter::repeat_n(dark, width as usize));
+    }
+
+    BitMatrix::from_row(&row, bar_height.max(1))
+}
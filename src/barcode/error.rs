@@ -0,0 +1,86 @@
+//! Everything encoding, rendering and reading barcodes can fail with.
+
+use crate::pass::BarcodeFormat;
+
+/// Why a barcode could not be encoded, rendered or read.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BarcodeError {
+    /// `messageEncoding` names no character set, or one that can only be decoded.
+    UnknownEncoding(String),
+    /// The message has a character its encoding can't represent.
+    Unrepresentable {
+        encoding: String,
+        character: char,
+    },
+    /// The symbology can't hold an empty message.
+    Empty(BarcodeFormat),
+    /// The message doesn't fit the largest symbol.
+    TooLong(BarcodeFormat),
+    /// The picture is blank.
+    NoBarcode,
+    /// The picture doesn't hold a symbol of this symbology.
+    WrongFormat(BarcodeFormat),
+    /// A symbol whose function patterns or check words are off. Damage isn't corrected.
+    Damaged(BarcodeFormat),
+    /// A symbol whose data doesn't decode, e.g. a mode the symbology doesn't define.
+    InvalidData {
+        format: BarcodeFormat,
+        message: String,
+    },
+    /// A symbol using a part of the symbology that isn't supported.
+    Unsupported {
+        format: BarcodeFormat,
+        feature: &'static str,
+    },
+    /// The picture could be read neither as a QR code nor as an Aztec code.
+    Unreadable {
+        qr: Box<BarcodeError>,
+        aztec: Box<BarcodeError>,
+    },
+    Qr(qrcode::types::QrError),
+    Image(image::ImageError),
+}
+
+fn format_name(format: BarcodeFormat) -> &'static str {
+    match format {
+        BarcodeFormat::Qr => "QR",
+        BarcodeFormat::Pdf417 => "PDF417",
+        BarcodeFormat::Aztec => "Aztec",
+        BarcodeFormat::Code128 => "Code 128",
+    }
+}
+
+impl std::fmt::Display for BarcodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownEncoding(encoding) => write!(f, "cannot encode messages as {encoding:?}"),
+            Self::Unrepresentable { encoding, character } => write!(f, "character {character:?} is not in {encoding}"),
+            Self::Empty(format) => write!(f, "{} codes cannot be empty", format_name(*format)),
+            Self::TooLong(format) => write!(f, "message is too long for {} codes", format_name(*format)),
+            Self::NoBarcode => write!(f, "no barcode found in the image"),
+            Self::WrongFormat(format) => write!(f, "no {} code found", format_name(*format)),
+            Self::Damaged(format) => write!(f, "{} code is damaged", format_name(*format)),
+            Self::InvalidData { format, message } => write!(f, "invalid {} code: {message}", format_name(*format)),
+            Self::Unsupported { format, feature } => write!(f, "{feature} are not supported in {} codes", format_name(*format)),
+            Self::Unreadable { qr, aztec } => write!(f, "could not read the barcode, as a QR code: {qr}, as an Aztec code: {aztec}"),
+            Self::Qr(e) => write!(f, "{e}"),
+            Self::Image(e) => write!(f, "invalid image: {e}"),
+        }
+    }
+}
+
+// Wrapped errors are part of the message, so aren't repeated as sources.
+impl std::error::Error for BarcodeError {}
+
+impl From<qrcode::types::QrError> for BarcodeError {
+    fn from(e: qrcode::types::QrError) -> Self {
+        Self::Qr(e)
+    }
+}
+
+impl From<image::ImageError> for BarcodeError {
+    fn from(e: image::ImageError) -> Self {
+        Self::Image(e)
+    }
+}
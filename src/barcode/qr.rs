@@ -0,0 +1,236 @@
+//! Reading QR codes back. Placement and block layout come from the `qrcode` encoder, so only
+//! the bitstream is decoded here.
+
+use qrcode::{
+    bits::Bits,
+    canvas::{Canvas, MaskPattern, Module},
+    ec::construct_codewords,
+    EcLevel, Version,
+};
+
+use super::{BarcodeError, BitMatrix};
+use crate::pass::BarcodeFormat;
+
+const MASKS: [MaskPattern; 8] = [
+    MaskPattern::Checkerboard,
+    MaskPattern::HorizontalLines,
+    MaskPattern::VerticalLines,
+    MaskPattern::DiagonalLines,
+    MaskPattern::LargeCheckerboard,
+    MaskPattern::Fields,
+    MaskPattern::Diamonds,
+    MaskPattern::Meadow,
+];
+
+const ALPHANUMERIC: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Data modules in placement order, up and down two column strips from the right, stepping
+/// over the vertical timing pattern.
+fn data_modules(canvas: &Canvas, width: i16) -> Vec<(i16, i16)> {
+    let mut modules = vec![];
+    let mut right = width - 1;
+    let mut upward = true;
+    while right > 0 {
+        if right == 6 {
+            right = 5;
+        }
+        for step in 0..width {
+            let y = if upward { width - 1 - step } else { step };
+            for x in [right, right - 1] {
+                if canvas.get(x, y) == Module::Empty {
+                    modules.push((x, y));
+                }
+            }
+        }
+        upward = !upward;
+        right -= 2;
+    }
+    modules
+}
+
+/// Reads bits, most significant first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: usize) -> Option<u32> {
+        if self.pos + count > self.bytes.len() * 8 {
+            return None;
+        }
+        let mut value = 0;
+        for _ in 0..count {
+            value = value << 1 | (self.bytes[self.pos / 8] >> (7 - self.pos % 8) & 1) as u32;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+}
+
+/// Decodes the segments of the data codewords into bytes.
+fn decode_segments(data: &[u8], version: i16) -> Result<Vec<u8>, BarcodeError> {
+    let truncated = || BarcodeError::InvalidData {
+        format: BarcodeFormat::Qr,
+        message: "data is truncated".to_owned(),
+    };
+    let size_class = match version {
+        1..=9 => 0,
+        10..=26 => 1,
+        _ => 2,
+    };
+    let mut reader = BitReader { bytes: data, pos: 0 };
+    let mut out = vec![];
+    // The terminator may be left out when the data fills the symbol.
+    while let Some(mode) = reader.read(4) {
+        match mode {
+            0b0000 => break,
+            // Numeric, three digits in ten bits.
+            0b0001 => {
+                let mut count = reader.read([10, 12, 14][size_class]).ok_or_else(truncated)?;
+                while count > 0 {
+                    let (digits, bits) = match count {
+                        1 => (1, 4),
+                        2 => (2, 7),
+                        _ => (3, 10),
+                    };
+                    let value = reader.read(bits).ok_or_else(truncated)?;
+                    out.extend(format!("{value:0digits$}").bytes());
+                    count -= digits as u32;
+                }
+            },
+            // Alphanumeric, two characters in eleven bits.
+            0b0010 => {
+                let mut count = reader.read([9, 11, 13][size_class]).ok_or_else(truncated)?;
+                while count > 0 {
+                    let pair = count >= 2;
+                    let value = reader.read(if pair { 11 } else { 6 }).ok_or_else(truncated)? as usize;
+                    let chars = if pair { vec![value / 45, value % 45] } else { vec![value] };
+                    for c in chars {
+                        let Some(&c) = ALPHANUMERIC.get(c) else {
+                            return Err(BarcodeError::InvalidData {
+                                format: BarcodeFormat::Qr,
+                                message: format!("invalid alphanumeric value {c}"),
+                            });
+                        };
+                        out.push(c);
+                    }
+                    count -= if pair { 2 } else { 1 };
+                }
+            },
+            0b0100 => {
+                let count = reader.read([8, 16, 16][size_class]).ok_or_else(truncated)?;
+                for _ in 0..count {
+                    out.push(reader.read(8).ok_or_else(truncated)? as u8);
+                }
+            },
+            // An ECI designator, which doesn't change the bytes.
+            0b0111 => {
+                let first = reader.read(8).ok_or_else(truncated)?;
+                let more = match first {
+                    0..=0x7f => 0,
+                    0x80..=0xbf => 8,
+                    _ => 16,
+                };
+                reader.read(more).ok_or_else(truncated)?;
+            },
+            // Structured append and FNC1 markers.
+            0b0011 => {
+                reader.read(16).ok_or_else(truncated)?;
+            },
+            0b0101 => {},
+            0b1001 => {
+                reader.read(8).ok_or_else(truncated)?;
+            },
+            0b1000 => return Err(BarcodeError::Unsupported {
+                format: BarcodeFormat::Qr,
+                feature: "kanji segments",
+            }),
+            _ => return Err(BarcodeError::InvalidData {
+                format: BarcodeFormat::Qr,
+                message: format!("invalid mode {mode:#06b}"),
+            }),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Decodes an upright symbol. Damaged symbols are rejected rather than corrected.
+pub(super) fn decode(matrix: &BitMatrix) -> Result<Vec<u8>, BarcodeError> {
+    let width = matrix.width();
+    if matrix.height() != width || width < 21 || !(width - 17).is_multiple_of(4) || width > 177 {
+        return Err(BarcodeError::WrongFormat(BarcodeFormat::Qr));
+    }
+    let version_number = ((width - 17) / 4) as i16;
+    let version = Version::Normal(version_number);
+    let dark = |x: i16, y: i16| matrix.get(x as usize, y as usize);
+
+    // Finder patterns are quick to check, and rule out most pictures that aren't QR codes.
+    let finder = |left: usize, top: usize| (0..7).all(|y| (0..7).all(|x| {
+        let ring = x.min(y).min(6 - x).min(6 - y);
+        matrix.get(left + x, top + y) == (ring != 1)
+    }));
+    if !(finder(0, 0) && finder(width - 7, 0) && finder(0, width - 7)) {
+        return Err(BarcodeError::WrongFormat(BarcodeFormat::Qr));
+    }
+
+    // Function patterns are the same whatever the level and mask, so any canvas gives the layout.
+    let mut layout = Canvas::new(version, EcLevel::L);
+    layout.draw_all_functional_patterns();
+    let modules = data_modules(&layout, width as i16);
+
+    // Format information says which error correction level and mask the symbol uses. Drawing
+    // each candidate and comparing it with the function patterns finds it.
+    let mut best: Option<(usize, EcLevel, Canvas)> = None;
+    for ec_level in [EcLevel::L, EcLevel::M, EcLevel::Q, EcLevel::H] {
+        for mask in MASKS {
+            let mut canvas = Canvas::new(version, ec_level);
+            canvas.draw_all_functional_patterns();
+            canvas.apply_mask(mask);
+            let mut mismatches = 0;
+            for y in 0..width as i16 {
+                for x in 0..width as i16 {
+                    if qrcode::canvas::is_functional(version, width as i16, x, y) && canvas.get(x, y).is_dark() != dark(x, y) {
+                        mismatches += 1;
+                    }
+                }
+            }
+            if best.as_ref().is_none_or(|(best_mismatches, ..)| mismatches < *best_mismatches) {
+                best = Some((mismatches, ec_level, canvas));
+            }
+        }
+    }
+    let Some((mismatches, ec_level, mask_canvas)) = best else {
+        return Err(BarcodeError::WrongFormat(BarcodeFormat::Qr));
+    };
+    if mismatches > 0 {
+        return Err(BarcodeError::Damaged(BarcodeFormat::Qr));
+    }
+
+    // Empty modules took the mask's color, so unmasking is comparing against it.
+    let mut codewords = vec![0u8; modules.len() / 8];
+    for (idx, &(x, y)) in modules.iter().take(codewords.len() * 8).enumerate() {
+        if dark(x, y) != mask_canvas.get(x, y).is_dark() {
+            codewords[idx / 8] |= 0x80 >> (idx % 8);
+        }
+    }
+
+    // Blocks are interleaved codeword by codeword. Encoding codeword numbers shows the order.
+    let data_len = Bits::new(version).max_len(ec_level)? / 8;
+    let low: Vec<u8> = (0..data_len).map(|idx| idx as u8).collect();
+    let high: Vec<u8> = (0..data_len).map(|idx| (idx >> 8) as u8).collect();
+    let (low, _) = construct_codewords(&low, version, ec_level)?;
+    let (high, _) = construct_codewords(&high, version, ec_level)?;
+    let mut data = vec![0; data_len];
+    for (idx, (&low, &high)) in low.iter().zip(&high).enumerate() {
+        data[(high as usize) << 8 | low as usize] = codewords[idx];
+    }
+
+    let (_, check_words) = construct_codewords(&data, version, ec_level)?;
+    if codewords.get(data_len..data_len + check_words.len()) != Some(check_words.as_slice()) {
+        return Err(BarcodeError::Damaged(BarcodeFormat::Qr));
+    }
+
+    decode_segments(&data, version_number)
+}
@@ -0,0 +1,159 @@
+//! Converting a pass in one go, the way the command line tool does: read it in one format, fill
+//! in what the other format needs and write it.
+
+use std::io::Write;
+
+use image::GrayImage;
+
+use crate::barcode;
+use crate::pass::{BarcodeFormat, ConversionReport, GoogleWalletPass, Pass, PassError, PassFormat, PassImage, PassImageKind, PkPass, Signer};
+
+/// A pass to convert, in the form it came in.
+#[derive(Debug)]
+pub enum Source {
+    PkPass(Box<PkPass>),
+    GPass(Box<GoogleWalletPass>),
+    /// A bare boarding pass barcode message, shown again as `format`. The flight's year is
+    /// worked out unless `year` is given.
+    Bcbp {
+        message: String,
+        format: BarcodeFormat,
+        year: Option<i32>,
+    },
+}
+
+impl Source {
+    /// A boarding pass barcode read off a picture, shown as `format` or else the symbology it
+    /// was read in.
+    pub fn from_bcbp_image(image: &GrayImage, format: Option<BarcodeFormat>, year: Option<i32>) -> Result<Self, PassError> {
+        let (found_format, data) = barcode::read(image)?;
+        // BCBP messages are ASCII, anything else is left for the parser to reject.
+        let message = data.iter().map(|&b| b as char).collect();
+
+        Ok(Self::Bcbp {
+            message,
+            format: format.unwrap_or(found_format),
+            year,
+        })
+    }
+
+    /// The format a pass converts to unless told otherwise: the other one, and pkpass for
+    /// barcodes.
+    pub fn default_target(&self) -> PassFormat {
+        match self {
+            Self::PkPass(_) => PassFormat::GPass,
+            Self::GPass(_) | Self::Bcbp { .. } => PassFormat::PkPass,
+        }
+    }
+
+    /// Converts to the neutral pass, reporting what it has no place for.
+    pub fn into_pass(self) -> Result<(Pass, ConversionReport), PassError> {
+        match self {
+            Self::PkPass(pkpass) => Ok(Pass::from_pkpass(*pkpass)),
+            Self::GPass(gpass) => Ok(Pass::from_gpass(*gpass)),
+            Self::Bcbp { message, format, year } => Ok((Pass::from_bcbp(&message, format, year)?, ConversionReport::default())),
+        }
+    }
+}
+
+/// What to put in the pass in place of what its source says, or doesn't: barcodes don't name
+/// their issuer, and Google passes have no team or icon.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub organization_name: Option<String>,
+    pub type_identifier: Option<String>,
+    pub team_identifier: Option<String>,
+    pub google_issuer_id: Option<String>,
+    /// Replaces any icons the pass has.
+    pub icon: Option<PassImage>,
+}
+
+impl Overrides {
+    pub fn apply(self, pass: &mut Pass) {
+        if let Some(organization_name) = self.organization_name {
+            pass.issuer.organization_name = organization_name;
+        }
+        if let Some(type_identifier) = self.type_identifier {
+            pass.type_identifier = type_identifier;
+        }
+        if self.team_identifier.is_some() {
+            pass.issuer.team_identifier = self.team_identifier;
+        }
+        if self.google_issuer_id.is_some() {
+            pass.issuer.google_issuer_id = self.google_issuer_id;
+        }
+        if let Some(icon) = self.icon {
+            pass.images.retain(|i| i.kind != PassImageKind::Icon);
+            pass.images.push(icon);
+        }
+    }
+}
+
+/// The format to write, and what writing it takes as [`Pass::write`] describes.
+#[derive(Clone, Copy)]
+pub struct Target<'a> {
+    pub format: PassFormat,
+    pub signer: Option<&'a Signer>,
+    pub image_base_url: Option<&'a str>,
+}
+
+/// Converts `source` with `overrides` applied and writes it as `target` to `output`. Returns
+/// everything reading and writing reported.
+pub fn convert<W: Write>(source: Source, overrides: Overrides, target: &Target, output: W) -> Result<ConversionReport, PassError> {
+    let (mut pass, mut report) = source.into_pass()?;
+    overrides.apply(&mut pass);
+    let written = pass.write(output, target.format, target.signer, target.image_base_url)?;
+    report.merge(written);
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pass::ManifestCheck;
+
+    fn icon() -> PassImage {
+        let mut data = vec![];
+        image::DynamicImage::new_rgba8(29, 29).write_to(&mut data, image::ImageOutputFormat::Png).unwrap();
+        PassImage {
+            kind: PassImageKind::Icon,
+            scale: 1,
+            locale: None,
+            data,
+        }
+    }
+
+    #[test]
+    fn overrides_fill_in_a_barcode() {
+        let source = || Source::Bcbp {
+            message: "M1DESMARAIS/LUC       EABC123 YULFRAAC 0834 226F001A0025 100".to_owned(),
+            format: BarcodeFormat::Aztec,
+            year: Some(2026),
+        };
+        assert_eq!(source().default_target(), PassFormat::PkPass);
+        let target = Target {
+            format: PassFormat::PkPass,
+            signer: None,
+            image_base_url: None,
+        };
+        let err = convert(source(), Overrides::default(), &target, vec![]).unwrap_err();
+        assert!(matches!(err, PassError::MissingIcon), "{err}");
+
+        let overrides = Overrides {
+            organization_name: Some("Air Canada".to_owned()),
+            type_identifier: Some("pass.com.example.boarding".to_owned()),
+            team_identifier: Some("ABCDE12345".to_owned()),
+            google_issuer_id: None,
+            icon: Some(icon()),
+        };
+        let mut output = vec![];
+        let report = convert(source(), overrides, &target, &mut output).unwrap();
+        assert!(report.is_lossless());
+
+        let pkpass = PkPass::read(std::io::Cursor::new(output), None, ManifestCheck::Strict).unwrap();
+        assert_eq!(pkpass.base.organization_name, "Air Canada");
+        assert_eq!(pkpass.base.pass_type_identifier, "pass.com.example.boarding");
+        assert_eq!(pkpass.images.iter().map(|i| i.pkpass_path()).collect::<Vec<_>>(), ["icon.png"]);
+    }
+}
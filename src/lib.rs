@@ -0,0 +1,27 @@
+//! Converts passes between Apple Wallet's pkpass archives and Google Wallet's JSON, by way of the
+//! format-neutral [`Pass`].
+//!
+//! - Reading: [`PkPass::read`], [`GoogleWalletPass::read`], and [`Pass::from_bcbp`] for a bare
+//!   boarding pass barcode, with [`barcode::read`] to get it off a picture.
+//! - Converting: [`Pass::from_pkpass`] and [`Pass::from_gpass`], which report what didn't fit,
+//!   and back with [`Pass::to_pkpass`], which needs an icon or a logo to make one from, and
+//!   [`Pass::to_gpass`], which needs the Google issuer id and a URL to host images at.
+//! - Writing: [`Pass::write`] in either format, or [`PkPass::write`], signed by a [`Signer`].
+//! - Rendering: [`barcode::render`] draws a barcode, and [`pass::PassBarcode::render`] one off a
+//!   pass.
+//! - All at once: [`convert::convert`] reads a [`convert::Source`] and writes it in another
+//!   format, as the command line tool does.
+//! - Validation: [`PkPass::read`] checks the manifest as its [`ManifestCheck`] says and keeps what
+//!   it found in [`PkPass::manifest_issues`], [`PkPass::verify_signature`] checks the signature
+//!   and [`PkPass::check_signature`] insists on it,
+//!   and [`pass::PkPassFlight::bcbp_mismatches`] lists where a boarding pass disagrees with its
+//!   barcode.
+//!
+//! Everything in [`pass`] fails with a [`PassError`], and everything in [`barcode`] with a
+//! [`barcode::BarcodeError`].
+
+pub mod barcode;
+pub mod convert;
+pub mod pass;
+
+pub use pass::{ConversionReport, ConversionWarning, GoogleWalletPass, ManifestCheck, Pass, PassError, PassFormat, PkPass, SignatureVerdict, Signer};
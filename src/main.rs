@@ -1,21 +1,32 @@
-mod pass;
+use std::fs::File;
 
-use std::{fs::File, io::{Stdin, Write, BufWriter}};
-
-use chrono::Datelike;
-use image::Luma;
-use pass::{Pass, PkPass, GoogleWalletPass, PassFormat};
-use qrcode::QrCode;
-
-use crate::pass::PkPassSubtype;
-
-fn main() {
-    println!("Hello, world!");
+use anyhow::{bail, Context};
+use image::GenericImageView;
+use pass_converter_rs::{barcode::{ErrorCorrection, RenderOptions}, convert::{convert, Overrides, Source, Target}, pass::{self, PkPass, GoogleWalletPass, PassError, PassFormat, BarcodeFormat, ManifestCheck, SignatureVerdict, Signer, PkPassSubtype, PassImage, PassImageKind, ConversionReport}};
 
+fn main() -> Result<(), anyhow::Error> {
     let mut found_pkpass = None;
     let mut found_gpass = None;
+    let mut found_bcbp = None;
+    let mut target = None;
+    let mut barcode_format = None;
     let mut mode = "basic".to_owned();
     let mut output = None;
+    let mut locale = None;
+    let mut manifest_check = ManifestCheck::Lenient;
+    let mut wwdr = None;
+    let mut certificate = None;
+    let mut key = None;
+    let mut password = None;
+    let mut unsigned = false;
+    let mut issuer_id = None;
+    let mut organization = None;
+    let mut pass_type_id = None;
+    let mut team_id = None;
+    let mut image_base_url = None;
+    let mut icon = None;
+    let mut year = None;
+    let mut render_options = RenderOptions::default();
     let mut args_iter = std::env::args();
     // Skip executable name.
     args_iter.next();
@@ -23,166 +34,403 @@ fn main() {
         match arg.as_str() {
             "--mode" | "-m" => {
                 let Some(path) = args_iter.next() else {
-                    println!("Missing mode.");
-                    return;
+                    bail!("Missing mode.");
                 };
 
                 mode = path;
             },
             "--output" | "-o" => {
                 let Some(path) = args_iter.next() else {
-                    println!("Missing output.");
-                    return;
+                    bail!("Missing output.");
                 };
 
                 output = Some(path);
             },
+            "--strict" => {
+                manifest_check = ManifestCheck::Strict;
+            },
+            "--wwdr" => {
+                let Some(path) = args_iter.next() else {
+                    bail!("Missing WWDR certificate file.");
+                };
+
+                wwdr = Some(path);
+            },
+            "--cert" => {
+                let Some(path) = args_iter.next() else {
+                    bail!("Missing pass certificate file.");
+                };
+
+                certificate = Some(path);
+            },
+            "--key" => {
+                let Some(path) = args_iter.next() else {
+                    bail!("Missing private key file.");
+                };
+
+                key = Some(path);
+            },
+            "--password" => {
+                let Some(p) = args_iter.next() else {
+                    bail!("Missing password.");
+                };
+
+                password = Some(p);
+            },
+            "--unsigned" => {
+                unsigned = true;
+            },
+            "--issuer-id" => {
+                let Some(id) = args_iter.next().filter(|s| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())) else {
+                    bail!("Missing or invalid Google Wallet issuer id, expected a number.");
+                };
+
+                issuer_id = Some(id);
+            },
+            "--organization" => {
+                let Some(name) = args_iter.next().filter(|s| !s.is_empty()) else {
+                    bail!("Missing organization name.");
+                };
+
+                organization = Some(name);
+            },
+            "--pass-type-id" => {
+                let Some(id) = args_iter.next().filter(|s| !s.is_empty()) else {
+                    bail!("Missing pass type identifier.");
+                };
+
+                pass_type_id = Some(id);
+            },
+            "--team-id" => {
+                let Some(id) = args_iter.next().filter(|s| !s.is_empty()) else {
+                    bail!("Missing team identifier.");
+                };
+
+                team_id = Some(id);
+            },
+            "--image-base-url" => {
+                let Some(url) = args_iter.next() else {
+                    bail!("Missing image base URL.");
+                };
+
+                image_base_url = Some(url);
+            },
+            "--icon" => {
+                let Some(path) = args_iter.next() else {
+                    bail!("Missing file for icon.");
+                };
+
+                icon = Some(path);
+            },
+            "--year" => {
+                let Some(y) = args_iter.next().and_then(|s| s.parse().ok()) else {
+                    bail!("Missing or invalid flight year.");
+                };
+
+                year = Some(y);
+            },
+            "--locale" | "-l" => {
+                let Some(l) = args_iter.next() else {
+                    bail!("Missing locale.");
+                };
+
+                locale = Some(l);
+            },
+            "--quiet-zone" => {
+                let Some(quiet_zone) = args_iter.next().and_then(|s| s.parse().ok()) else {
+                    bail!("Missing or invalid quiet zone.");
+                };
+
+                render_options.quiet_zone = Some(quiet_zone);
+            },
+            "--module-size" => {
+                let Some(module_size) = args_iter.next().and_then(|s| s.parse().ok()) else {
+                    bail!("Missing or invalid module size.");
+                };
+
+                render_options.module_size = module_size;
+            },
+            "--ec-level" => {
+                let Some(error_correction) = args_iter.next().and_then(|s| ErrorCorrection::from_name(&s)) else {
+                    bail!("Missing or invalid error correction level, expected one of L, M, Q or H.");
+                };
+
+                render_options.error_correction = error_correction;
+            },
             "--gpass" | "-g" => {
                 let Some(gpass) = args_iter.next() else {
-                    println!("Missing file for gpass.");
-                    return;
+                    bail!("Missing file for gpass.");
                 };
 
                 found_gpass = Some(gpass);
             },
             "--pkpass" | "-p" => {
                 let Some(pkpass) = args_iter.next() else {
-                    println!("Missing file for pkpass.");
-                    return;
+                    bail!("Missing file for pkpass.");
                 };
 
                 found_pkpass = Some(pkpass);
             },
+            "--bcbp" => {
+                let Some(message) = args_iter.next() else {
+                    bail!("Missing BCBP message.");
+                };
+
+                found_bcbp = Some(BcbpInput::Message(message));
+            },
+            "--bcbp-image" => {
+                let Some(path) = args_iter.next() else {
+                    bail!("Missing file for BCBP barcode image.");
+                };
+
+                found_bcbp = Some(BcbpInput::Image(path));
+            },
+            "--barcode-format" => {
+                let Some(format) = args_iter.next().and_then(|s| BarcodeFormat::from_name(&s)) else {
+                    bail!("Missing or invalid barcode format, expected one of qr, pdf417, aztec or code128.");
+                };
+
+                barcode_format = Some(format);
+            },
+            "--target" => {
+                let Some(format) = args_iter.next().and_then(|s| match s.as_str() {
+                    "pkpass" => Some(PassFormat::PkPass),
+                    "gpass" => Some(PassFormat::GPass),
+                    _ => None,
+                }) else {
+                    bail!("Missing or invalid target, expected pkpass or gpass.");
+                };
+
+                target = Some(format);
+            },
             _ => {
-                println!("Unknown argument found: {arg:?}.");
-                return;
+                bail!("Unknown argument found: {arg:?}.");
             }
         }
     }
 
-    let (pass, target_format) = match (found_pkpass, found_gpass) {
-        (Some(pkpass_path), None) => {
-            let pkpass = match open_pkpass(pkpass_path.as_str()) {
-                Ok(pkpass) => pkpass,
-                Err(e) => {
-                    println!("Opening and reading provided pkpass at {pkpass_path:?} failed due to {e:?}.");
-                    return;
-                },
-            };
-
-            if let PkPassSubtype::Flight(ref flight) = pkpass.subtype {
-                println!("boarding time: {}", flight.parsed_boarding_datetime().unwrap().to_rfc2822());
-                println!("departure time: {}", flight.parsed_departure_datetime().unwrap().to_rfc2822());
-                let google_json = serde_json::json!({
-                    "flightClasses": [{
-                        "id": pkpass.base.serial_number, // {issuer}.{id}
-
-                        "origin": {
-                          "gate": None::<String>,
-                          "airportIataCode": flight.board_point(),
-                        },
-                        "destination": {
-                          "airportIataCode": flight.off_point(),
-                        },
-                        "flightHeader": {
-                          "flightNumber": flight.subsidiary_carrier().map(|s| &s[2..]),
-                          "carrier": {
-                            "carrierIataCode": flight.subsidiary_carrier().map(|s| &s[..2]),
-                            "airlineLogo": None::<String>,
-                          },
-                        },
-                        "localScheduledDepartureDateTime": flight.parsed_departure_datetime().unwrap().to_rfc2822(),
-                    }],
-                    "flightObjects": [{
-                        "id": pkpass.base.serial_number, // {issuer}.{id}
-                        "classId": pkpass.base.pass_type_identifier, // {issuer}.{typeid}
-
-                        "barcode": {
-                            "type": "AZTEC",
-                            "value": pkpass.barcode.message,
-                        },
-
-                        "passengerName": flight.passenger(),
-                        "boardingAndSeatingInfo": {
-                          "seatNumber": flight.seat(),
-                          "seatClass": flight.booking_class()
-                        },
-                        "reservationInfo": {
-                          "confirmationCode": flight.recloc()
-                        }
-                    }]
-                });
-                println!("gpass: {google_json:#?}");
+    let source = match (found_pkpass, found_gpass, found_bcbp) {
+        (Some(pkpass_path), None, None) => {
+            let mut pkpass = open_pkpass(pkpass_path.as_str(), year, manifest_check)
+                .with_context(|| format!("Opening and reading provided pkpass at {pkpass_path:?} failed"))?;
+            for issue in pkpass.manifest_issues.iter() {
+                eprintln!("Manifest mismatch: {issue}.");
+            }
+            if let PkPassSubtype::Flight(flight) = &pkpass.subtype {
+                let source = flight.year_source();
+                eprintln!("Flight year {} from {source}, {} confidence.", flight.year(), source.confidence());
+                for mismatch in flight.bcbp_mismatches() {
+                    eprintln!("Barcode mismatch: {mismatch}.");
+                }
             }
-            // TODO make this pretty
-            if mode == "gen" {
-                let barcode = &pkpass.barcode;
-                let p = output.unwrap_or_else(|| "tmp".to_owned());
-                let f_path = if p.ends_with(".png") {
-                    p
-                } else {
-                    format!("{p}.png")
-                };
-                let data: Vec<_> = barcode.message.as_bytes().iter().copied().collect();
-
-                let code = QrCode::new(data).expect("built");
-                let image = code.render::<Luma<u8>>().build();
-                image.save(f_path).expect("image save complete");
-
-                todo!("merge this with the proper fork join flow");
+            if let Some(wwdr_path) = wwdr.as_deref() {
+                // Strict checks insist on a valid signature, as they do on a matching manifest.
+                let verdict = verify_pkpass(&pkpass, wwdr_path, manifest_check == ManifestCheck::Strict)
+                    .with_context(|| format!("Verifying the signature against {wwdr_path:?} failed"))?;
+                eprintln!("Signature: {verdict}.");
             }
+            pkpass.localize(locale.as_deref());
 
-            println!("Parsed pkpass: {pkpass:#?}");
+            Source::PkPass(Box::new(pkpass))
+        },
+        (None, Some(gpass_path), None) => {
+            let gpass = open_gpass(gpass_path.as_str())
+                .with_context(|| format!("Opening and reading provided gpass at {gpass_path:?} failed"))?;
 
-            (Pass::from(pkpass), PassFormat::GPass)
+            Source::GPass(Box::new(gpass))
         },
-        (None, Some(gpass)) => {
-            unimplemented!("gpass to pkpass not implemented.");
+        (None, None, Some(bcbp)) => {
+            open_bcbp(&bcbp, barcode_format, year)
+                .with_context(|| format!("Reading the boarding pass barcode {bcbp} failed"))?
         },
-        (None, None) => {
-            println!("No pass provided.");
-            return;
+        (None, None, None) => {
+            bail!("No pass provided.");
         },
-        (Some(_pkpass), Some(_gpass)) => {
-            println!("Only one of pkpass or gpass should be presented.");
-            return;
+        _ => {
+            bail!("Only one of pkpass, gpass or BCBP should be presented.");
         },
     };
 
-    let res = match output {
-        Some(p) => {
-            let f = match File::create(p) {
-                Ok(f) => f,
-                Err(e) => {
-                    println!("Opening file failed due to {e:?}.");
-                    return;
-                },
+    // TODO make this pretty
+    if mode == "gen" {
+        let (pass, _) = source.into_pass().context("Reading the pass failed")?;
+        let Some(barcode) = pass.barcodes.first() else {
+            bail!("Pass has no barcode.");
+        };
+        let p = output.unwrap_or_else(|| "tmp".to_owned());
+        let f_path = if p.ends_with(".png") {
+            p
+        } else {
+            format!("{p}.png")
+        };
+
+        let image = barcode.render(&render_options).context("Rendering barcode failed")?;
+        image.save(&f_path).with_context(|| format!("Saving the barcode to {f_path:?} failed"))?;
+        eprintln!("Barcode written to {f_path:?}.");
+
+        return Ok(());
+    }
+
+    let target_format = target.unwrap_or(source.default_target());
+
+    // Wallet refuses unsigned passes, so leaving the signature out has to be asked for.
+    let signer = match (target_format, certificate, unsigned) {
+        (PassFormat::PkPass, Some(_), true) => {
+            bail!("Only one of --cert or --unsigned should be presented.");
+        },
+        (PassFormat::PkPass, Some(certificate_path), false) => {
+            let Some(wwdr_path) = wwdr else {
+                bail!("Signing needs the WWDR certificate, given with --wwdr.");
             };
-            write_pass(f, pass, target_format)
+            let signer = open_signer(&certificate_path, key.as_deref(), password.as_deref(), &wwdr_path)
+                .with_context(|| format!("Loading the pass certificate at {certificate_path:?} failed"))?;
+            Some(signer)
         },
-        None => {
-            write_pass(std::io::stdout(), pass, target_format)
+        (PassFormat::PkPass, None, false) => {
+            bail!("Writing a pkpass needs a certificate given with --cert, or --unsigned.");
         },
+        _ => None,
+    };
+
+    let icon = match icon {
+        Some(icon_path) => Some(open_icon(&icon_path).with_context(|| format!("Reading the icon at {icon_path:?} failed"))?),
+        None => None,
+    };
+    let overrides = Overrides {
+        organization_name: organization,
+        type_identifier: pass_type_id,
+        team_identifier: team_id,
+        google_issuer_id: issuer_id,
+        icon,
+    };
+    let target = Target {
+        format: target_format,
+        signer: signer.as_ref(),
+        image_base_url: image_base_url.as_deref(),
     };
-    let outcome = match res {
-        Ok(outcome) => outcome,
-        Err(e) => {
-            println!("Outputting file format failed due to {e:?}.");
+    let report = match output {
+        Some(p) => {
+            let f = File::create(&p).with_context(|| format!("Opening output file {p:?} failed"))?;
+            convert(source, overrides, &target, f)
+        },
+        None => {
+            convert(source, overrides, &target, std::io::stdout().lock())
         },
+    }.map_err(with_hint)?;
+    print_report(&report);
+
+    eprintln!("Success.");
+
+    Ok(())
+}
+
+/// Points at the option giving what the pass was missing, if there is one.
+fn with_hint(e: PassError) -> anyhow::Error {
+    let option = match &e {
+        PassError::MissingIdentifier("organizationName" | "issuerName") => "--organization",
+        PassError::MissingIdentifier("passTypeIdentifier" | "classId") => "--pass-type-id",
+        PassError::MissingIdentifier("teamIdentifier") => "--team-id",
+        PassError::MissingIssuerId | PassError::InvalidIssuerId(_) => "--issuer-id",
+        PassError::MissingIcon => "--icon",
+        _ => return anyhow::Error::new(e).context("Converting the pass failed"),
     };
+    anyhow::Error::new(e).context(format!("Converting the pass failed, give what's missing with {option}"))
+}
+
+fn print_report(report: &ConversionReport) {
+    for dropped in report.dropped.iter() {
+        eprintln!("Dropped {} = {}", dropped.path, dropped.value);
+    }
+    for warning in report.warnings.iter() {
+        eprintln!("Warning: {warning}");
+    }
+}
 
-    println!("Success.");
+/// Reads a PNG icon, its scale guessed from its width in 29 point steps.
+fn open_icon(path: &str) -> Result<PassImage, anyhow::Error> {
+    let data = std::fs::read(path)?;
+    let width = image::load_from_memory_with_format(&data, image::ImageFormat::Png)?.width();
+
+    Ok(PassImage {
+        kind: PassImageKind::Icon,
+        scale: (width / 29).clamp(1, 3) as u8,
+        locale: None,
+        data,
+    })
 }
 
-fn open_pkpass(path: &str) -> Result<PkPass, anyhow::Error> {
+fn open_pkpass(path: &str, year: Option<i32>, manifest_check: ManifestCheck) -> Result<PkPass, anyhow::Error> {
     let f = File::open(path)?;
 
-    let now = chrono::Utc::now();
-    let pkpass = PkPass::read(f, now.year())?;
+    let pkpass = PkPass::read(f, year, manifest_check)?;
 
     Ok(pkpass)
 }
 
-fn write_pass(output: impl Write, pass: Pass, format: PassFormat) -> Result<(), anyhow::Error> {
-    Ok(())
+/// Checks the signature, failing unless it's valid when `strict`.
+fn verify_pkpass(pkpass: &PkPass, wwdr_path: &str, strict: bool) -> Result<SignatureVerdict, anyhow::Error> {
+    let wwdr = std::fs::read(wwdr_path)?;
+    let wwdr = pass::signature::read_certificates(&wwdr)?;
+
+    if strict {
+        Ok(pkpass.check_signature(&wwdr)?)
+    } else {
+        Ok(pkpass.verify_signature(&wwdr)?)
+    }
+}
+
+/// Loads a `.p12`, or a PEM certificate with its key from `key_path` or the same file.
+fn open_signer(certificate_path: &str, key_path: Option<&str>, password: Option<&str>, wwdr_path: &str) -> Result<Signer, anyhow::Error> {
+    let wwdr = std::fs::read(wwdr_path)?;
+    let wwdr = pass::signature::read_certificates(&wwdr)?;
+    let certificate = std::fs::read(certificate_path)?;
+
+    if certificate.windows(11).any(|w| w == b"-----BEGIN ") {
+        let key = match key_path {
+            Some(key_path) => std::fs::read(key_path)?,
+            None => certificate.clone(),
+        };
+        Ok(Signer::from_pem(&certificate, &key, password, wwdr)?)
+    } else {
+        Ok(Signer::from_pkcs12(&certificate, password.unwrap_or_default(), wwdr)?)
+    }
+}
+
+/// A boarding pass barcode, as its message or a picture of it.
+enum BcbpInput {
+    Message(String),
+    Image(String),
+}
+
+impl std::fmt::Display for BcbpInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Message(message) => write!(f, "{message:?}"),
+            Self::Image(path) => write!(f, "at {path:?}"),
+        }
+    }
+}
+
+/// Takes a boarding pass barcode, shown as `format` or else the symbology it was read in.
+/// Messages given as text default to Aztec, which most airlines use on phones.
+fn open_bcbp(bcbp: &BcbpInput, format: Option<BarcodeFormat>, year: Option<i32>) -> Result<Source, anyhow::Error> {
+    match bcbp {
+        BcbpInput::Message(message) => Ok(Source::Bcbp {
+            message: message.clone(),
+            format: format.unwrap_or(BarcodeFormat::Aztec),
+            year,
+        }),
+        BcbpInput::Image(path) => {
+            let image = image::open(path)?.into_luma8();
+            Ok(Source::from_bcbp_image(&image, format, year)?)
+        },
+    }
+}
+
+fn open_gpass(path: &str) -> Result<GoogleWalletPass, anyhow::Error> {
+    let f = File::open(path)?;
+
+    let gpass = GoogleWalletPass::read(f)?;
+
+    Ok(gpass)
 }
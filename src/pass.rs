@@ -1,37 +1,705 @@
-use std::{io::{Read, Seek}, collections::HashMap};
-
-use zip::result::ZipResult;
-
+pub mod airport;
+pub mod bcbp;
+pub mod error;
+pub mod flight_year;
+pub mod gpass;
+pub mod signature;
+mod strings;
+#[cfg(test)]
+mod fixtures;
+
+use std::{io::{Read, Seek, Write}, collections::{BTreeMap, HashMap}};
+
+use chrono::Datelike;
+use sha1::{Digest, Sha1};
+
+pub use error::PassError;
+pub use flight_year::YearSource;
+pub use gpass::GoogleWalletPass;
+pub use signature::{SignatureVerdict, Signer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PassFormat {
     GPass,
     PkPass,
 }
 
+/// Format-neutral pass. Every conversion pivots through this, so it holds everything pkpass and
+/// Google Wallet have in common.
 #[derive(Debug, Clone)]
 pub struct Pass {
+    pub serial_number: String,
+    pub type_identifier: String,
+    pub issuer: PassIssuer,
+
+    pub description: String,
+    pub logo_text: Option<String>,
+    pub voided: bool,
+
+    pub colors: PassColors,
+    pub barcodes: Vec<PassBarcode>,
+    pub fields: PassFields,
+    pub dates: PassDates,
+    pub locations: Vec<PassLocation>,
+    pub images: Vec<PassImage>,
+    pub localizations: PassLocalizations,
+
+    pub category: PassCategory,
+}
+
+/// Translations of the text on a pass. Languages are BCP 47 tags, e.g. `pt-BR`.
+#[derive(Debug, Clone, Default)]
+pub struct PassLocalizations {
+    /// Language the text of the pass is in, when known.
+    pub language: Option<String>,
+    /// Translations of a text, by the text as the pass shows it and then by language.
+    pub translations: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// BCP 47 language tag for a locale, e.g. `pt-BR` for the `pt_BR.lproj` directory.
+fn language_tag(locale: &str) -> String {
+    locale.replace('_', "-")
+}
+
+/// `.lproj` directory name for a BCP 47 language tag, e.g. `pt_BR` for `pt-BR`.
+fn lproj_name(tag: &str) -> String {
+    tag.replace('-', "_")
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PassIssuer {
+    pub organization_name: String,
+    /// Apple developer team that owns the pass type identifier.
+    pub team_identifier: Option<String>,
+    /// Numeric Google Wallet issuer id, used to prefix class and object ids. Writing a Google
+    /// Wallet pass fails without it.
+    pub google_issuer_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PassColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl PassColor {
+    /// Parses either the pkpass `rgb(r, g, b)` form or the Google `#rrggbb` form.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            if hex.len() != 6 {
+                return None;
+            }
+            let channel = |idx: usize| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok();
+            return Some(Self {
+                r: channel(0)?,
+                g: channel(2)?,
+                b: channel(4)?,
+            });
+        }
+
+        let inner = s.strip_prefix("rgb(")?.strip_suffix(')')?;
+        let mut channels = inner.split(',').map(|c| c.trim().parse::<u8>().ok());
+        let color = Self {
+            r: channels.next()??,
+            g: channels.next()??,
+            b: channels.next()??,
+        };
+        if channels.next().is_some() {
+            return None;
+        }
+        Some(color)
+    }
+
+    pub fn to_rgb_string(self) -> String {
+        format!("rgb({}, {}, {})", self.r, self.g, self.b)
+    }
+
+    pub fn to_hex_string(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PassColors {
+    pub background: Option<PassColor>,
+    pub foreground: Option<PassColor>,
+    pub label: Option<PassColor>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BarcodeFormat {
+    Qr,
+    Pdf417,
+    Aztec,
+    Code128,
+}
+
+impl BarcodeFormat {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "qr" => Some(Self::Qr),
+            "pdf417" => Some(Self::Pdf417),
+            "aztec" => Some(Self::Aztec),
+            "code128" => Some(Self::Code128),
+            _ => None,
+        }
+    }
+
+    pub fn from_pkpass_str(s: &str) -> Option<Self> {
+        match s {
+            "PKBarcodeFormatQR" => Some(Self::Qr),
+            "PKBarcodeFormatPDF417" => Some(Self::Pdf417),
+            "PKBarcodeFormatAztec" => Some(Self::Aztec),
+            "PKBarcodeFormatCode128" => Some(Self::Code128),
+            _ => None,
+        }
+    }
+
+    pub fn as_pkpass_str(self) -> &'static str {
+        match self {
+            Self::Qr => "PKBarcodeFormatQR",
+            Self::Pdf417 => "PKBarcodeFormatPDF417",
+            Self::Aztec => "PKBarcodeFormatAztec",
+            Self::Code128 => "PKBarcodeFormatCode128",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PassBarcode {
+    pub format: BarcodeFormat,
+    pub message: String,
+    /// IANA character set name the message is encoded with, usually `iso-8859-1`.
+    pub encoding: String,
+    pub alt_text: Option<String>,
+}
+
+impl PassBarcode {
+    /// Draws the barcode, as [`crate::barcode::render`] does.
+    pub fn render(&self, options: &crate::barcode::RenderOptions) -> Result<image::GrayImage, crate::barcode::BarcodeError> {
+        crate::barcode::render(self.format, &self.message, &self.encoding, options)
+    }
+}
+
+impl Pass {
+    /// Picks the image of `kind` to show where a format only has room for one: the sharpest one
+    /// that isn't localized, or is in the pass's language.
+    pub fn image(&self, kind: PassImageKind) -> Option<&PassImage> {
+        self.images.iter()
+            .filter(|i| i.kind == kind)
+            .filter(|i| i.locale.is_none() || i.locale == self.localizations.language)
+            .max_by_key(|i| i.scale)
+    }
+
+    /// Picks the barcode to show where `format` only has room for one. The others are kept
+    /// wherever the format allows.
+    pub fn primary_barcode(&self, format: PassFormat) -> Option<&PassBarcode> {
+        let not_linear = self.barcodes.iter().find(|b| b.format != BarcodeFormat::Code128);
+        match format {
+            // The legacy `barcode` key predates Code128 support, so it can't hold one at all.
+            PassFormat::PkPass => not_linear,
+            // Linear codes are hard to scan off a phone screen, but better than nothing.
+            PassFormat::GPass => not_linear.or(self.barcodes.first()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PassField {
+    pub key: String,
+    pub label: Option<String>,
+    pub value: String,
+}
+
+/// Field groups in the order they appear on the pass, front to back.
+#[derive(Debug, Clone, Default)]
+pub struct PassFields {
+    pub header: Vec<PassField>,
+    pub primary: Vec<PassField>,
+    pub secondary: Vec<PassField>,
+    pub auxiliary: Vec<PassField>,
+    pub back: Vec<PassField>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PassDates {
+    /// When the pass becomes relevant, e.g. shown on the lock screen.
+    pub relevant: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub expiration: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+/// A time on the local clock, with its UTC offset when the source gave one. Google Wallet takes
+/// flight times, and may take others, without an offset, and those must stay as written rather
+/// than be pinned to a zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalDateTime {
+    pub local: chrono::NaiveDateTime,
+    pub offset: Option<chrono::FixedOffset>,
+}
+
+impl LocalDateTime {
+    pub fn naive(local: chrono::NaiveDateTime) -> Self {
+        Self {
+            local,
+            offset: None,
+        }
+    }
+
+    pub fn date(&self) -> chrono::NaiveDate {
+        self.local.date()
+    }
+
+    /// The instant this is, when the offset is known.
+    pub fn to_fixed(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.local.and_local_timezone(self.offset?).single()
+    }
+
+    /// ISO 8601, with the offset only when it's known.
+    pub fn to_iso8601(&self) -> String {
+        match self.to_fixed() {
+            Some(dt) => dt.to_rfc3339(),
+            None => self.local.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        }
+    }
+
+    /// Parses an ISO 8601 datetime with or without an offset, and with or without the seconds.
+    pub fn parse(s: &str) -> Option<Self> {
+        if let Some(dt) = parse_w3c_datetime(s) {
+            return Some(dt.into());
+        }
+        chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+            .or_else(|_| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M"))
+            .ok()
+            .map(Self::naive)
+    }
+}
+
+impl From<chrono::DateTime<chrono::FixedOffset>> for LocalDateTime {
+    fn from(dt: chrono::DateTime<chrono::FixedOffset>) -> Self {
+        Self {
+            local: dt.naive_local(),
+            offset: Some(*dt.offset()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PassLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+    pub relevant_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PassImageKind {
+    Icon,
+    Logo,
+    Strip,
+    Thumbnail,
+    Background,
+    Footer,
+}
+
+impl PassImageKind {
+    pub const ALL: [Self; 6] = [
+        Self::Icon,
+        Self::Logo,
+        Self::Strip,
+        Self::Thumbnail,
+        Self::Background,
+        Self::Footer,
+    ];
+
+    pub fn from_file_stem(stem: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.file_stem() == stem)
+    }
+
+    /// File name stem used inside a pkpass, e.g. `logo` for `logo@2x.png`.
+    pub fn file_stem(self) -> &'static str {
+        match self {
+            Self::Icon => "icon",
+            Self::Logo => "logo",
+            Self::Strip => "strip",
+            Self::Thumbnail => "thumbnail",
+            Self::Background => "background",
+            Self::Footer => "footer",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PassImage {
+    pub kind: PassImageKind,
+    /// 1, 2 or 3 for the @1x, @2x and @3x variants.
+    pub scale: u8,
+    /// BCP 47 tag of the localization the image belongs to, `None` for the unlocalized image.
+    pub locale: Option<String>,
+    pub data: Vec<u8>,
+}
+
+impl PassImage {
+    /// Reads an image from its path inside a pkpass, `[<locale>.lproj/]<kind>[@2x|@3x].png`.
+    /// Anything else isn't a pass image.
+    pub fn from_pkpass_path(path: &str, data: Vec<u8>) -> Option<Self> {
+        let (locale, name) = match path.split_once(".lproj/") {
+            Some((locale, name)) => (Some(language_tag(locale)), name),
+            None => (None, path),
+        };
+        let name = name.strip_suffix(".png")?;
+        let (stem, scale) = match name.split_once('@') {
+            Some((stem, "2x")) => (stem, 2),
+            Some((stem, "3x")) => (stem, 3),
+            Some(_) => return None,
+            None => (name, 1),
+        };
+
+        Some(Self {
+            kind: PassImageKind::from_file_stem(stem)?,
+            scale,
+            locale,
+            data,
+        })
+    }
+
+    pub fn pkpass_path(&self) -> String {
+        let locale = self.locale.as_ref().map(|l| format!("{}.lproj/", lproj_name(l))).unwrap_or_default();
+        let scale = if self.scale > 1 {
+            format!("@{}x", self.scale)
+        } else {
+            String::new()
+        };
+        format!("{locale}{}{scale}.png", self.kind.file_stem())
+    }
+}
+
+/// Category specific payload. Anything that does not fit here stays in [`PassFields`].
+#[derive(Debug, Clone)]
+pub enum PassCategory {
+    Flight(FlightDetails),
+    Transit(TransitDetails),
+    Event(EventDetails),
+    Coupon(CouponDetails),
+    Loyalty(LoyaltyDetails),
+    GiftCard(GiftCardDetails),
+    Generic,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FlightDetails {
+    /// IATA carrier code, e.g. `UA`.
+    pub carrier: Option<String>,
+    pub flight_number: Option<String>,
+    /// IATA airport code of the departure airport.
+    pub origin: Option<String>,
+    /// IATA airport code of the arrival airport.
+    pub destination: Option<String>,
+    pub origin_name: Option<String>,
+    pub destination_name: Option<String>,
+    pub origin_terminal: Option<String>,
+    pub origin_gate: Option<String>,
+
+    /// Departure date, for flights whose times aren't known.
+    pub date: Option<chrono::NaiveDate>,
+    pub boarding: Option<LocalDateTime>,
+    pub departure: Option<LocalDateTime>,
+    pub arrival: Option<LocalDateTime>,
+
+    pub passenger_name: Option<String>,
+    pub seat: Option<String>,
+    pub seat_class: Option<String>,
+    pub boarding_group: Option<String>,
+    pub sequence_number: Option<String>,
+    pub status: Option<String>,
+
+    pub confirmation_code: Option<String>,
+    pub ticket_number: Option<String>,
+    pub frequent_flyer_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransitMode {
+    Train,
+    Bus,
+    Boat,
+    #[default]
+    Generic,
+}
+
+/// Ground and sea transit. Flights have [`FlightDetails`] instead.
+#[derive(Debug, Clone, Default)]
+pub struct TransitDetails {
+    pub mode: TransitMode,
+    pub operator_name: Option<String>,
+    /// Line or route, e.g. `Northeast Regional`.
+    pub route: Option<String>,
+
+    /// Station code or short name.
+    pub origin: Option<String>,
+    pub origin_name: Option<String>,
+    pub destination: Option<String>,
+    pub destination_name: Option<String>,
+
+    pub departure: Option<LocalDateTime>,
+    pub arrival: Option<LocalDateTime>,
+
+    pub platform: Option<String>,
+    pub carriage: Option<String>,
+    pub coach: Option<String>,
+    pub seat: Option<String>,
+
+    pub passenger_name: Option<String>,
+    pub ticket_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EventDetails {
+    pub event_name: Option<String>,
+    pub venue_name: Option<String>,
+    pub venue_address: Option<String>,
+
+    pub start: Option<LocalDateTime>,
+    pub end: Option<LocalDateTime>,
+
+    pub gate: Option<String>,
+    pub section: Option<String>,
+    pub row: Option<String>,
+    pub seat: Option<String>,
+
+    pub ticket_holder: Option<String>,
+    pub ticket_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CouponDetails {
+    pub title: Option<String>,
+    pub provider: Option<String>,
+    pub details: Option<String>,
+    pub fine_print: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LoyaltyDetails {
+    pub program_name: Option<String>,
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    pub points_label: Option<String>,
+    pub points_balance: Option<String>,
+}
+
+/// An amount of money, kept as the decimal string the pass shows so nothing is lost to rounding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassMoney {
+    pub amount: String,
+    /// ISO 4217 currency code, e.g. `USD`.
+    pub currency_code: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GiftCardDetails {
+    pub merchant_name: Option<String>,
+    pub card_number: Option<String>,
+    pub pin: Option<String>,
+    pub balance: Option<PassMoney>,
+}
+
+/// Values from the source pass that a conversion had nowhere to put, and anything it had to
+/// convert on less than the source said.
+#[derive(Debug, Clone, Default)]
+pub struct ConversionReport {
+    pub dropped: Vec<DroppedValue>,
+    pub warnings: Vec<ConversionWarning>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DroppedValue {
+    /// JSON path into the source document, e.g. `boardingPass.headerFields[0].changeMessage`.
+    pub path: String,
+    pub value: serde_json::Value,
+}
+
+/// Something a conversion got through, but not the way the source meant it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConversionWarning {
+    /// An airport missing from the airport table, so its times have no offset.
+    UnknownAirport(String),
+    /// A `pass.strings` file that couldn't be parsed, so its translations are missing.
+    InvalidStrings {
+        file: String,
+        message: String,
+    },
+    /// An image left out of a Google Wallet pass for want of a URL to host it at, by its pkpass
+    /// path.
+    UnhostedImage(String),
+    /// A pkpass icon made from the logo, as the pass had none.
+    IconFromLogo,
+    /// A flight known only by its date, whose departure Google Wallet needs a time for. The
+    /// departure is left out, so Google won't take the pass until it's given one.
+    UnknownDepartureTime(chrono::NaiveDate),
+    /// A gift card balance left out of a Google Wallet pass, as it isn't a plain decimal amount
+    /// with at most 6 decimals.
+    InvalidBalance(String),
+}
+
+impl std::fmt::Display for ConversionWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownAirport(code) => write!(f, "unknown airport {code:?}, its times are kept without a time zone"),
+            Self::InvalidStrings { file, message } => write!(f, "{file:?} is invalid and was skipped: {message}"),
+            Self::UnhostedImage(file) => write!(f, "{file:?} was left out, Google Wallet needs images hosted at a URL"),
+            Self::IconFromLogo => write!(f, "the pass has no icon, one was made from its logo"),
+            Self::UnknownDepartureTime(date) => write!(f, "the flight on {date} has no departure time, which Google Wallet requires"),
+            Self::InvalidBalance(amount) => write!(f, "balance {amount:?} is not a decimal amount and was left out"),
+        }
+    }
+}
+
+impl ConversionReport {
+    pub fn is_lossless(&self) -> bool {
+        self.dropped.is_empty() && self.warnings.is_empty()
+    }
+
+    /// Adds what a later step of the same conversion reported.
+    pub fn merge(&mut self, other: ConversionReport) {
+        self.dropped.extend(other.dropped);
+        for warning in other.warnings {
+            self.warn(warning);
+        }
+    }
+
+    fn warn(&mut self, warning: ConversionWarning) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    fn drop_value(&mut self, path: impl Into<String>, value: serde_json::Value) {
+        self.dropped.push(DroppedValue {
+            path: path.into(),
+            value,
+        });
+    }
+
+    /// Records every entry of `obj` whose key is not in `known`.
+    fn drop_unknown_keys(&mut self, path: &str, obj: &serde_json::Map<String, serde_json::Value>, known: &[&str]) {
+        for (key, value) in obj {
+            if known.contains(&key.as_str()) {
+                continue;
+            }
+            self.drop_value(json_path(path, key), value.clone());
+        }
+    }
+
+    /// Parses `raw`, recording it as dropped if it is present but unparseable.
+    fn parse_or_drop<T>(&mut self, path: &str, raw: Option<&str>, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        let raw = raw?;
+        let parsed = parse(raw);
+        if parsed.is_none() {
+            self.drop_value(path, raw.into());
+        }
+        parsed
+    }
+}
+
+/// `key` of the dictionary at `path`.
+fn json_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn expect_object<'a>(value: &'a serde_json::Value, path: &str) -> Result<&'a serde_json::Map<String, serde_json::Value>, PassError> {
+    value.as_object().ok_or_else(|| PassError::WrongType {
+        path: path.to_owned(),
+        expected: "an object",
+    })
+}
+
+/// Reads `key` of the dictionary at `path` with `read`, which gives `None` unless the value is
+/// `expected`.
+fn required<'a, T>(
+    obj: &'a serde_json::Map<String, serde_json::Value>,
+    path: &str,
+    key: &str,
+    expected: &'static str,
+    read: impl FnOnce(&'a serde_json::Value) -> Option<T>,
+) -> Result<T, PassError> {
+    let Some(value) = obj.get(key) else {
+        return Err(PassError::MissingKey {
+            path: json_path(path, key),
+        });
+    };
+    read(value).ok_or_else(|| PassError::WrongType {
+        path: json_path(path, key),
+        expected,
+    })
+}
+
+fn required_str(obj: &serde_json::Map<String, serde_json::Value>, path: &str, key: &str) -> Result<String, PassError> {
+    required(obj, path, key, "a string", |v| v.as_str().map(|s| s.to_owned()))
+}
+
+/// Reads an optional W3C datetime, kept as written.
+fn optional_date(obj: &serde_json::Map<String, serde_json::Value>, path: &str, key: &str) -> Result<Option<String>, PassError> {
+    let Some(date) = obj.get(key).and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    if parse_w3c_datetime(date).is_none() {
+        return Err(PassError::InvalidDate {
+            path: json_path(path, key),
+            value: date.to_owned(),
+        });
+    }
+
+    Ok(Some(date.to_owned()))
+}
+
+/// Parses the W3C datetimes used by `pass.json`, which may leave out the seconds.
+fn parse_w3c_datetime(s: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    let s = match s.strip_suffix('Z') {
+        Some(utc) => format!("{utc}+00:00"),
+        None => s.to_owned(),
+    };
+    chrono::DateTime::parse_from_rfc3339(&s).ok()
+        .or_else(|| chrono::DateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M%:z").ok())
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SubtypeKey {
     Flight,
+    Transit,
+    EventTicket,
+    Coupon,
+    StoreCard,
+    Generic,
 }
 
 impl SubtypeKey {
-    const KEYS: [Self; 1] = [
+    const KEYS: [Self; 6] = [
         SubtypeKey::Flight,
+        SubtypeKey::Transit,
+        SubtypeKey::EventTicket,
+        SubtypeKey::Coupon,
+        SubtypeKey::StoreCard,
+        SubtypeKey::Generic,
     ];
 }
 
-trait PassSubtypeOwner {
+/// A pass style, kept in `pass.json` under one of several keys.
+pub trait PassSubtypeOwner {
+    /// The key `json` holds `key`'s style under, if it holds that style.
     fn check_for_key(key: SubtypeKey, json: &serde_json::Map<String, serde_json::Value>) -> Option<&'static str>;
 
-    /// Extracts a single subtype.
-    fn extract_subtype<'a>(json: &'a serde_json::Value) -> Result<(SubtypeKey, &'a serde_json::Value), &'static str> {
+    /// Extracts a single subtype, with the key it is under.
+    fn extract_subtype(json: &serde_json::Value) -> Result<(SubtypeKey, &'static str, &serde_json::Value), PassError> {
         let mut found_information = vec![];
 
-        let Some(obj) = json.as_object() else {
-            return Err("JSON is not an object.");
-        };
+        let obj = expect_object(json, "")?;
 
         for key in SubtypeKey::KEYS {
             let Some(subtype_key) = Self::check_for_key(key, obj) else {
@@ -41,66 +709,174 @@ trait PassSubtypeOwner {
                 continue;
             };
 
-            found_information.push((key, subtype_entry));
+            found_information.push((key, subtype_key, subtype_entry));
         }
 
         match found_information.len() {
-            0 => Err("Missing subtype"),
+            0 => Err(PassError::UnsupportedSubtype),
             1 => Ok(found_information.remove(0)),
-            _ => Err("More than one subtype"),
+            _ => Err(PassError::MultipleSubtypes(found_information.iter().map(|(_, key, _)| *key).collect())),
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct GoogleWalletPass {
-}
-
 #[derive(Debug, Clone)]
 pub struct PkPass {
-    // serde_json: serde_json::Value,
-    // l10n: HashMap<String, HashMap<String, Vec<u8>>>,
+    /// `pass.json` as it was read, kept so conversions can report what they had to leave behind.
+    /// `None` for passes built in memory.
+    pub json: Option<serde_json::Value>,
+    /// `pass.strings` of every `.lproj` directory, by the directory's locale.
+    pub l10n: BTreeMap<String, BTreeMap<String, String>>,
+    /// `pass.strings` files left out of [`Self::l10n`] because they couldn't be parsed, with why.
+    pub invalid_strings: BTreeMap<String, String>,
+    /// Locale of [`Self::l10n`] the text was put in by [`Self::localize`].
+    pub locale: Option<String>,
+    /// What didn't match `manifest.json` when the pass was read leniently.
+    pub manifest_issues: Vec<ManifestIssue>,
+    /// `manifest.json` and its detached `signature` as they were read, `None` for passes built
+    /// in memory.
+    pub manifest: Option<Vec<u8>>,
+    pub signature: Option<Vec<u8>>,
 
     pub base: PkPassBase,
-    pub barcode: PkPassBarcode,
+    /// In order of preference, as listed in `barcodes` or else the legacy `barcode` key.
+    pub barcodes: Vec<PkPassBarcode>,
+    pub images: Vec<PassImage>,
     pub subtype: PkPassSubtype,
 }
 
+/// How [`PkPass::read`] treats a pass whose files don't match its `manifest.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ManifestCheck {
+    /// Refuse the pass.
+    Strict,
+    /// Read it anyway, keeping the mismatches in [`PkPass::manifest_issues`].
+    #[default]
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestIssue {
+    MissingManifest,
+    InvalidManifest(String),
+    /// Listed in the manifest but not in the archive.
+    MissingFile(String),
+    HashMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+    /// In the archive but not listed in the manifest.
+    UnlistedFile(String),
+}
+
+impl std::fmt::Display for ManifestIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingManifest => write!(f, "manifest.json is missing"),
+            Self::InvalidManifest(e) => write!(f, "manifest.json is invalid: {e}"),
+            Self::MissingFile(file) => write!(f, "{file:?} is in the manifest but not the pass"),
+            Self::HashMismatch { file, expected, actual } => write!(f, "{file:?} has SHA-1 {actual}, the manifest expects {expected}"),
+            Self::UnlistedFile(file) => write!(f, "{file:?} is not in the manifest"),
+        }
+    }
+}
+
+impl ManifestIssue {
+    /// Files that sign the manifest, so can't be listed in it.
+    const UNLISTED_FILES: [&'static str; 2] = ["manifest.json", "signature"];
+
+    /// Checks every file of the archive against `manifest.json`.
+    fn check(files: &HashMap<String, Vec<u8>>) -> Vec<Self> {
+        let Some(manifest) = files.get("manifest.json") else {
+            return vec![Self::MissingManifest];
+        };
+        let manifest: BTreeMap<String, String> = match serde_json::from_slice(manifest) {
+            Ok(manifest) => manifest,
+            Err(e) => return vec![Self::InvalidManifest(e.to_string())],
+        };
+
+        let mut issues = vec![];
+        for (file, expected) in manifest.iter() {
+            let Some(data) = files.get(file) else {
+                issues.push(Self::MissingFile(file.clone()));
+                continue;
+            };
+            let actual = format!("{:x}", Sha1::digest(data));
+            if !actual.eq_ignore_ascii_case(expected) {
+                issues.push(Self::HashMismatch {
+                    file: file.clone(),
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        let mut unlisted: Vec<_> = files.keys()
+            .filter(|file| !manifest.contains_key(*file) && !Self::UNLISTED_FILES.contains(&file.as_str()))
+            .collect();
+        unlisted.sort();
+        issues.extend(unlisted.into_iter().map(|file| Self::UnlistedFile(file.clone())));
+
+        issues
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PkPassBarcode {
     pub format: String,
     pub message: String,
     pub encoding: String,
+    pub alt_text: Option<String>,
 }
 
 impl PkPassBarcode {
-    fn extract(json: &serde_json::Value, year: i32) -> Result<Self, anyhow::Error> {
-        let Some(base_obj) = json.as_object() else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "Expected json to be object.".to_owned()).into());
-        };
-        let Some(base_obj) = base_obj.get("barcode") else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBarcode is missing key".to_owned()).into());
-        };
-        let Some(obj) = base_obj.as_object() else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "Expected barcode to be object.".to_owned()).into());
-        };
+    /// Keys of a barcode dictionary that are read.
+    const KEYS: [&'static str; 4] = ["format", "message", "messageEncoding", "altText"];
+
+    /// Reads `barcodes`, falling back to the `barcode` key that predates it. Passes without either
+    /// have no barcode.
+    pub fn extract_all(json: &serde_json::Value) -> Result<Vec<Self>, PassError> {
+        let obj = expect_object(json, "")?;
+
+        if obj.contains_key("barcodes") {
+            let barcodes = required(obj, "", "barcodes", "an array", |v| v.as_array())?;
+            return barcodes.iter()
+                .enumerate()
+                .map(|(idx, barcode)| Self::extract(barcode, &format!("barcodes[{idx}]")))
+                .collect();
+        }
+        match obj.get("barcode") {
+            Some(barcode) => Ok(vec![Self::extract(barcode, "barcode")?]),
+            None => Ok(vec![]),
+        }
+    }
 
-        let Some(format) = obj.get("format").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBarcode is missing key".to_owned()).into());
-        };
-        let Some(message) = obj.get("message").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBarcode is missing key".to_owned()).into());
-        };
-        let Some(encoding) = obj.get("messageEncoding").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBarcode is missing key".to_owned()).into());
-        };
+    fn extract(value: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(value, path)?;
+
+        let format = required_str(obj, path, "format")?;
+        let message = required_str(obj, path, "message")?;
+        let encoding = required_str(obj, path, "messageEncoding")?;
+        let alt_text = obj.get("altText").and_then(|v| v.as_str()).map(|s| s.to_owned());
 
         Ok(Self {
             format,
             message,
             encoding,
+            alt_text,
         })
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut obj = serde_json::Map::new();
+        obj.insert("format".to_owned(), self.format.clone().into());
+        obj.insert("message".to_owned(), self.message.clone().into());
+        obj.insert("messageEncoding".to_owned(), self.encoding.clone().into());
+        if let Some(alt_text) = &self.alt_text {
+            obj.insert("altText".to_owned(), alt_text.clone().into());
+        }
+        obj.into()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -135,37 +911,56 @@ pub struct PkPassBase {
 
     pub background_color: String,
     pub foreground_color: String,
+    pub label_color: Option<String>,
+
+    pub logo_text: Option<String>,
+    pub relevant_date: Option<String>,
+    pub expiration_date: Option<String>,
+    pub voided: bool,
+    pub locations: Vec<PkPassLocation>,
 }
 
 impl PkPassBase {
-    fn extract(json: &serde_json::Value, year: i32) -> Result<Self, anyhow::Error> {
-        let Some(obj) = json.as_object() else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "Expected json to be object.".to_owned()).into());
-        };
+    /// Top level `pass.json` keys that are read.
+    const KEYS: [&'static str; 16] = [
+        "serialNumber",
+        "formatVersion",
+        "passTypeIdentifier",
+        "organizationName",
+        "teamIdentifier",
+        "description",
+        "backgroundColor",
+        "foregroundColor",
+        "labelColor",
+        "logoText",
+        "relevantDate",
+        "expirationDate",
+        "voided",
+        "locations",
+        "barcode",
+        "barcodes",
+    ];
 
-        let Some(serial_number) = obj.get("serialNumber").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBase is missing key".to_owned()).into());
-        };
-        let Some(format_version) = obj.get("formatVersion").and_then(|v| v.as_u64()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBase is missing key".to_owned()).into());
-        };
-        let Some(pass_type_identifier) = obj.get("passTypeIdentifier").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBase is missing key".to_owned()).into());
-        };
-        let Some(organization_name) = obj.get("organizationName").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBase is missing key".to_owned()).into());
-        };
-        let Some(team_identifier) = obj.get("teamIdentifier").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBase is missing key".to_owned()).into());
-        };
-        let Some(description) = obj.get("description").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBase is missing key".to_owned()).into());
-        };
-        let Some(background_color) = obj.get("backgroundColor").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBase is missing key".to_owned()).into());
-        };
-        let Some(foreground_color) = obj.get("foregroundColor").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassBase is missing key".to_owned()).into());
+    /// Reads the keys every pass has from `pass.json`.
+    pub fn extract(json: &serde_json::Value) -> Result<Self, PassError> {
+        let obj = expect_object(json, "")?;
+
+        let serial_number = required_str(obj, "", "serialNumber")?;
+        let format_version = required(obj, "", "formatVersion", "an integer", |v| v.as_u64())?;
+        let pass_type_identifier = required_str(obj, "", "passTypeIdentifier")?;
+        let organization_name = required_str(obj, "", "organizationName")?;
+        let team_identifier = required_str(obj, "", "teamIdentifier")?;
+        let description = required_str(obj, "", "description")?;
+        let background_color = required_str(obj, "", "backgroundColor")?;
+        let foreground_color = required_str(obj, "", "foregroundColor")?;
+        let label_color = obj.get("labelColor").and_then(|v| v.as_str()).map(|s| s.to_owned());
+        let logo_text = obj.get("logoText").and_then(|v| v.as_str()).map(|s| s.to_owned());
+        let relevant_date = optional_date(obj, "", "relevantDate")?;
+        let expiration_date = optional_date(obj, "", "expirationDate")?;
+        let voided = obj.get("voided").and_then(|v| v.as_bool()).unwrap_or(false);
+        let locations = match obj.get("locations").and_then(|v| v.as_array()) {
+            Some(locations) => PkPassLocation::extract_array(locations, "locations")?,
+            None => vec![],
         };
 
         Ok(Self {
@@ -177,193 +972,1323 @@ impl PkPassBase {
             description,
             background_color,
             foreground_color,
+            label_color,
+            logo_text,
+            relevant_date,
+            expiration_date,
+            voided,
+            locations,
+        })
+    }
+
+    fn to_json(&self, obj: &mut serde_json::Map<String, serde_json::Value>) {
+        obj.insert("serialNumber".to_owned(), self.serial_number.clone().into());
+        obj.insert("formatVersion".to_owned(), self.format_version.into());
+        obj.insert("passTypeIdentifier".to_owned(), self.pass_type_identifier.clone().into());
+        obj.insert("organizationName".to_owned(), self.organization_name.clone().into());
+        obj.insert("teamIdentifier".to_owned(), self.team_identifier.clone().into());
+        obj.insert("description".to_owned(), self.description.clone().into());
+        obj.insert("backgroundColor".to_owned(), self.background_color.clone().into());
+        obj.insert("foregroundColor".to_owned(), self.foreground_color.clone().into());
+        if let Some(label_color) = &self.label_color {
+            obj.insert("labelColor".to_owned(), label_color.clone().into());
+        }
+        if let Some(logo_text) = &self.logo_text {
+            obj.insert("logoText".to_owned(), logo_text.clone().into());
+        }
+        if let Some(relevant_date) = &self.relevant_date {
+            obj.insert("relevantDate".to_owned(), relevant_date.clone().into());
+        }
+        if let Some(expiration_date) = &self.expiration_date {
+            obj.insert("expirationDate".to_owned(), expiration_date.clone().into());
+        }
+        if self.voided {
+            obj.insert("voided".to_owned(), true.into());
+        }
+        if !self.locations.is_empty() {
+            obj.insert("locations".to_owned(), self.locations.iter().map(PkPassLocation::to_json).collect());
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PkPassLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+    pub relevant_text: Option<String>,
+}
+
+impl PkPassLocation {
+    /// Keys of a location dictionary that are read.
+    const KEYS: [&'static str; 4] = ["latitude", "longitude", "altitude", "relevantText"];
+
+    fn extract_array(value: &[serde_json::Value], path: &str) -> Result<Vec<Self>, PassError> {
+        value.iter()
+            .enumerate()
+            .map(|(idx, location)| Self::extract(location, &format!("{path}[{idx}]")))
+            .collect()
+    }
+
+    fn extract(value: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(value, path)?;
+
+        let latitude = required(obj, path, "latitude", "a number", |v| v.as_f64())?;
+        let longitude = required(obj, path, "longitude", "a number", |v| v.as_f64())?;
+        let altitude = obj.get("altitude").and_then(|v| v.as_f64());
+        let relevant_text = obj.get("relevantText").and_then(|v| v.as_str()).map(|s| s.to_owned());
+
+        Ok(Self {
+            latitude,
+            longitude,
+            altitude,
+            relevant_text,
         })
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut obj = serde_json::Map::new();
+        obj.insert("latitude".to_owned(), self.latitude.into());
+        obj.insert("longitude".to_owned(), self.longitude.into());
+        if let Some(altitude) = self.altitude {
+            obj.insert("altitude".to_owned(), altitude.into());
+        }
+        if let Some(relevant_text) = &self.relevant_text {
+            obj.insert("relevantText".to_owned(), relevant_text.clone().into());
+        }
+        obj.into()
+    }
 }
 
-#[derive(Debug, Clone)]
-pub enum PkPassSubtype {
-    Flight(PkPassFlight),
-}
+#[derive(Debug, Clone)]
+pub enum PkPassSubtype {
+    Flight(PkPassFlight),
+    Transit(PkPassTransit),
+    EventTicket(PkPassEventTicket),
+    Coupon(PkPassCoupon),
+    StoreCard(PkPassStoreCard),
+    Generic(PkPassGeneric),
+}
+
+impl PassSubtypeOwner for PkPassSubtype {
+    fn check_for_key(key: SubtypeKey, json: &serde_json::Map<String, serde_json::Value>) -> Option<&'static str> {
+        match key {
+            SubtypeKey::Flight => {
+                const KEY: &str = "boardingPass";
+                const SECONDARY_KEY: &str = "transitType";
+                const AIR_TYPE: &str = "PKTransitTypeAir";
+                let map = json.get(KEY).and_then(|j| j.as_object())?;
+                let transit_type = map.get(SECONDARY_KEY)?;
+                if transit_type.as_str() != Some(AIR_TYPE) {
+                    return None;
+                };
+                Some(KEY)
+            },
+            SubtypeKey::Transit => {
+                const KEY: &str = "boardingPass";
+                const SECONDARY_KEY: &str = "transitType";
+                const AIR_TYPE: &str = "PKTransitTypeAir";
+                // Any other boarding pass is transit, so a missing or unknown type is reported
+                // when it's extracted.
+                let map = json.get(KEY).and_then(|j| j.as_object())?;
+                if map.get(SECONDARY_KEY).and_then(|v| v.as_str()) == Some(AIR_TYPE) {
+                    return None;
+                };
+                Some(KEY)
+            },
+            SubtypeKey::EventTicket => {
+                const KEY: &str = "eventTicket";
+                json.get(KEY)?.as_object()?;
+                Some(KEY)
+            },
+            SubtypeKey::Coupon => {
+                const KEY: &str = "coupon";
+                json.get(KEY)?.as_object()?;
+                Some(KEY)
+            },
+            SubtypeKey::StoreCard => {
+                const KEY: &str = "storeCard";
+                json.get(KEY)?.as_object()?;
+                Some(KEY)
+            },
+            SubtypeKey::Generic => {
+                const KEY: &str = "generic";
+                json.get(KEY)?.as_object()?;
+                Some(KEY)
+            },
+        }
+    }
+}
+
+impl PkPassSubtype {
+    /// Key of the `pass.json` dictionary holding this subtype's fields.
+    fn json_key(&self) -> &'static str {
+        match self {
+            PkPassSubtype::Flight(_) | PkPassSubtype::Transit(_) => "boardingPass",
+            PkPassSubtype::EventTicket(_) => "eventTicket",
+            PkPassSubtype::Coupon(_) => "coupon",
+            PkPassSubtype::StoreCard(_) => "storeCard",
+            PkPassSubtype::Generic(_) => "generic",
+        }
+    }
+
+    fn fields_mut(&mut self) -> &mut PkPassFields {
+        match self {
+            PkPassSubtype::Flight(flight) => &mut flight.fields,
+            PkPassSubtype::Transit(transit) => &mut transit.fields,
+            PkPassSubtype::EventTicket(event) => &mut event.fields,
+            PkPassSubtype::Coupon(coupon) => &mut coupon.fields,
+            PkPassSubtype::StoreCard(store_card) => &mut store_card.fields,
+            PkPassSubtype::Generic(generic) => &mut generic.fields,
+        }
+    }
+
+    /// Top level `pass.json` keys read by this subtype, besides its own dictionary.
+    fn top_level_keys(&self) -> &'static [&'static str] {
+        match self {
+            PkPassSubtype::EventTicket(_) | PkPassSubtype::StoreCard(_) => &["semantics"],
+            PkPassSubtype::Flight(_)
+            | PkPassSubtype::Transit(_)
+            | PkPassSubtype::Coupon(_)
+            | PkPassSubtype::Generic(_) => &[],
+        }
+    }
+
+    /// Keys of the subtype dictionary that are read.
+    fn keys(&self) -> &'static [&'static str] {
+        match self {
+            PkPassSubtype::Flight(_) | PkPassSubtype::Transit(_) => &PkPassFlight::KEYS,
+            PkPassSubtype::EventTicket(_)
+            | PkPassSubtype::Coupon(_)
+            | PkPassSubtype::StoreCard(_)
+            | PkPassSubtype::Generic(_) => &FIELD_GROUP_KEYS,
+        }
+    }
+
+    /// Keys of a field dictionary that are read.
+    fn field_keys(&self) -> &'static [&'static str] {
+        match self {
+            // Balances carry their currency on the field.
+            PkPassSubtype::StoreCard(_) => &PkPassValue::CURRENCY_KEYS,
+            PkPassSubtype::Flight(_)
+            | PkPassSubtype::Transit(_)
+            | PkPassSubtype::EventTicket(_)
+            | PkPassSubtype::Coupon(_)
+            | PkPassSubtype::Generic(_) => &PkPassValue::KEYS,
+        }
+    }
+
+    /// Writes the subtype dictionary, and any top level keys the subtype owns, into `pass_obj`.
+    fn to_json(&self, pass_obj: &mut serde_json::Map<String, serde_json::Value>) {
+        let mut obj = serde_json::Map::new();
+        match self {
+            PkPassSubtype::Flight(flight) => {
+                flight.fields.to_json(&mut obj);
+                obj.insert("transitType".to_owned(), flight.transit_type.as_str().into());
+            },
+            PkPassSubtype::Transit(transit) => {
+                transit.fields.to_json(&mut obj);
+                obj.insert("transitType".to_owned(), transit.transit_type.as_str().into());
+            },
+            PkPassSubtype::EventTicket(event) => {
+                event.fields.to_json(&mut obj);
+                if let Some(semantics) = event.semantics.to_json() {
+                    pass_obj.insert("semantics".to_owned(), semantics);
+                }
+            },
+            PkPassSubtype::Coupon(coupon) => {
+                coupon.fields.to_json(&mut obj);
+            },
+            PkPassSubtype::StoreCard(store_card) => {
+                store_card.fields.to_json(&mut obj);
+                if let Some(semantics) = store_card.semantics.to_json() {
+                    pass_obj.insert("semantics".to_owned(), semantics);
+                }
+            },
+            PkPassSubtype::Generic(generic) => {
+                generic.fields.to_json(&mut obj);
+            },
+        }
+        pass_obj.insert(self.json_key().to_owned(), obj.into());
+    }
+
+    /// Reads the pass style from `pass.json`. Boarding passes take their flight's year from
+    /// `year_hints` and are checked against `bcbp`, the decoded barcode, when there is one.
+    pub fn extract(json: &serde_json::Value, year_hints: &flight_year::YearHints, bcbp: Option<bcbp::Bcbp>) -> Result<Self, PassError> {
+        let obj = expect_object(json, "")?;
+
+        let (key, path, v) = Self::extract_subtype(json)?;
+
+        let subtype = match key {
+            SubtypeKey::Flight => {
+                PkPassSubtype::Flight(PkPassFlight::extract(v, path, year_hints, bcbp)?)
+            },
+            SubtypeKey::Transit => {
+                PkPassSubtype::Transit(PkPassTransit::extract(v, path)?)
+            },
+            SubtypeKey::EventTicket => {
+                PkPassSubtype::EventTicket(PkPassEventTicket::extract(obj, v, path)?)
+            },
+            SubtypeKey::Coupon => {
+                PkPassSubtype::Coupon(PkPassCoupon::extract(v, path)?)
+            },
+            SubtypeKey::StoreCard => {
+                PkPassSubtype::StoreCard(PkPassStoreCard::extract(obj, v, path)?)
+            },
+            SubtypeKey::Generic => {
+                PkPassSubtype::Generic(PkPassGeneric::extract(v, path)?)
+            },
+        };
+
+        Ok(subtype)
+    }
+}
+
+/// Keys of the field groups shared by every subtype dictionary.
+const FIELD_GROUP_KEYS: [&str; 5] = [
+    "headerFields",
+    "primaryFields",
+    "secondaryFields",
+    "auxiliaryFields",
+    "backFields",
+];
+
+#[derive(Debug, Clone)]
+pub struct PkPassValue {
+    pub key: String,
+    pub label: Option<String>,
+    pub value: String,
+    /// ISO 4217 code when the value is an amount of money.
+    pub currency_code: Option<String>,
+}
+
+impl PkPassValue {
+    /// Keys of a field dictionary that are read.
+    const KEYS: [&'static str; 3] = ["key", "label", "value"];
+    /// [`Self::KEYS`] plus the currency code, for subtypes that read amounts of money.
+    const CURRENCY_KEYS: [&'static str; 4] = ["key", "label", "value", "currencyCode"];
+
+    fn extract_array(value: &[serde_json::Value], path: &str) -> Result<Vec<Self>, PassError> {
+        value.iter()
+            .enumerate()
+            .map(|(idx, field)| Self::extract(field, &format!("{path}[{idx}]")))
+            .collect()
+    }
+
+    fn extract(value: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(value, path)?;
+
+        let key = required_str(obj, path, "key")?;
+        let label = obj.get("label").and_then(|v| v.as_str()).map(|s| s.to_owned());
+        // Numbers are allowed as values, they are formatted by the device.
+        let value = required(obj, path, "value", "a string or number", |v| match v {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })?;
+        let currency_code = obj.get("currencyCode").and_then(|v| v.as_str()).map(|s| s.to_owned());
+
+        Ok(Self {
+          key,
+          label,
+          value,
+          currency_code,
+        })
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut obj = serde_json::Map::new();
+        obj.insert("key".to_owned(), self.key.clone().into());
+        if let Some(label) = &self.label {
+            obj.insert("label".to_owned(), label.clone().into());
+        }
+        obj.insert("value".to_owned(), self.value.clone().into());
+        if let Some(currency_code) = &self.currency_code {
+            obj.insert("currencyCode".to_owned(), currency_code.clone().into());
+        }
+        obj.into()
+    }
+
+    fn to_pass_fields(values: &[Self]) -> Vec<PassField> {
+        values.iter().map(|v| PassField {
+            key: v.key.clone(),
+            label: v.label.clone(),
+            value: v.value.clone(),
+        }).collect()
+    }
+
+    fn from_pass_fields(fields: &[PassField]) -> Vec<Self> {
+        fields.iter().map(|f| Self {
+            key: f.key.clone(),
+            label: f.label.clone(),
+            value: f.value.clone(),
+            currency_code: None,
+        }).collect()
+    }
+
+    /// Appends a field unless one with the same key is already present.
+    fn insert_missing(values: &mut Vec<Self>, key: &str, label: &str, value: Option<String>) {
+        let Some(value) = value else {
+            return;
+        };
+        if values.iter().any(|v| v.key == key) {
+            return;
+        }
+        values.push(Self {
+            key: key.to_owned(),
+            label: Some(label.to_owned()),
+            value,
+            currency_code: None,
+        });
+    }
+
+    fn scan_for_value_for_key<'a>(key: &str, iter: impl Iterator<Item=&'a Self>) -> Option<&'a str> {
+        for entry in iter {
+            if entry.key == key {
+                return Some(entry.value.as_str());
+            }
+        }
+        None
+    }
+}
+
+/// The field groups every subtype dictionary holds.
+#[derive(Debug, Clone, Default)]
+pub struct PkPassFields {
+    pub header_fields: Vec<PkPassValue>,
+    pub primary_fields: Vec<PkPassValue>,
+    pub secondary_fields: Vec<PkPassValue>,
+    pub auxiliary_fields: Vec<PkPassValue>,
+    pub back_fields: Vec<PkPassValue>,
+}
+
+impl PkPassFields {
+    fn extract(obj: &serde_json::Map<String, serde_json::Value>, path: &str) -> Result<Self, PassError> {
+        // Every group is optional.
+        let group = |key: &str| -> Result<Vec<PkPassValue>, PassError> {
+            if !obj.contains_key(key) {
+                return Ok(vec![]);
+            }
+            let values = required(obj, path, key, "an array", |v| v.as_array())?;
+            PkPassValue::extract_array(values, &json_path(path, key))
+        };
+
+        Ok(Self {
+            header_fields: group("headerFields")?,
+            primary_fields: group("primaryFields")?,
+            secondary_fields: group("secondaryFields")?,
+            auxiliary_fields: group("auxiliaryFields")?,
+            back_fields: group("backFields")?,
+        })
+    }
+
+    fn to_json(&self, obj: &mut serde_json::Map<String, serde_json::Value>) {
+        let groups = [
+            &self.header_fields,
+            &self.primary_fields,
+            &self.secondary_fields,
+            &self.auxiliary_fields,
+            &self.back_fields,
+        ];
+        for (key, values) in FIELD_GROUP_KEYS.into_iter().zip(groups) {
+            if !values.is_empty() {
+                obj.insert(key.to_owned(), values.iter().map(PkPassValue::to_json).collect());
+            }
+        }
+    }
+
+    fn to_pass_fields(&self) -> PassFields {
+        PassFields {
+            header: PkPassValue::to_pass_fields(&self.header_fields),
+            primary: PkPassValue::to_pass_fields(&self.primary_fields),
+            secondary: PkPassValue::to_pass_fields(&self.secondary_fields),
+            auxiliary: PkPassValue::to_pass_fields(&self.auxiliary_fields),
+            back: PkPassValue::to_pass_fields(&self.back_fields),
+        }
+    }
+
+    /// Every field of every group, front to back.
+    fn iter(&self) -> impl Iterator<Item=&PkPassValue> {
+        self.header_fields.iter()
+            .chain(self.primary_fields.iter())
+            .chain(self.secondary_fields.iter())
+            .chain(self.auxiliary_fields.iter())
+            .chain(self.back_fields.iter())
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item=&mut PkPassValue> {
+        self.header_fields.iter_mut()
+            .chain(self.primary_fields.iter_mut())
+            .chain(self.secondary_fields.iter_mut())
+            .chain(self.auxiliary_fields.iter_mut())
+            .chain(self.back_fields.iter_mut())
+    }
+
+    /// Looks a key up across every group, front to back.
+    fn value_for_key(&self, key: &str) -> Option<&str> {
+        PkPassValue::scan_for_value_for_key(key, self.iter())
+    }
+
+    fn from_pass_fields(fields: &PassFields) -> Self {
+        Self {
+            header_fields: PkPassValue::from_pass_fields(&fields.header),
+            primary_fields: PkPassValue::from_pass_fields(&fields.primary),
+            secondary_fields: PkPassValue::from_pass_fields(&fields.secondary),
+            auxiliary_fields: PkPassValue::from_pass_fields(&fields.auxiliary),
+            back_fields: PkPassValue::from_pass_fields(&fields.back),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PkPassGeneric {
+    fields: PkPassFields,
+}
+
+impl PkPassGeneric {
+    fn extract(internal_json: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(internal_json, path)?;
+
+        Ok(Self {
+            fields: PkPassFields::extract(obj, path)?,
+        })
+    }
+}
+
+/// A seat from the `seats` semantic tag.
+#[derive(Debug, Clone, Default)]
+pub struct PkPassSeat {
+    pub section: Option<String>,
+    pub row: Option<String>,
+    pub number: Option<String>,
+    pub identifier: Option<String>,
+    pub seat_type: Option<String>,
+}
+
+impl PkPassSeat {
+    /// Keys of a seat dictionary that are read.
+    const KEYS: [&'static str; 5] = ["seatSection", "seatRow", "seatNumber", "seatIdentifier", "seatType"];
+
+    fn extract(value: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(value, path)?;
+
+        let string = |key: &str| obj.get(key).and_then(|v| v.as_str()).map(|s| s.to_owned());
+        Ok(Self {
+            section: string("seatSection"),
+            row: string("seatRow"),
+            number: string("seatNumber"),
+            identifier: string("seatIdentifier"),
+            seat_type: string("seatType"),
+        })
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut obj = serde_json::Map::new();
+        let entries = [
+            ("seatSection", &self.section),
+            ("seatRow", &self.row),
+            ("seatNumber", &self.number),
+            ("seatIdentifier", &self.identifier),
+            ("seatType", &self.seat_type),
+        ];
+        for (key, value) in entries {
+            if let Some(value) = value {
+                obj.insert(key.to_owned(), value.clone().into());
+            }
+        }
+        obj.into()
+    }
+}
+
+/// The event semantic tags from the top level `semantics` dictionary.
+#[derive(Debug, Clone, Default)]
+pub struct PkPassEventSemantics {
+    pub event_name: Option<String>,
+    pub venue_name: Option<String>,
+    pub venue_entrance: Option<String>,
+    pub event_start_date: Option<String>,
+    pub event_end_date: Option<String>,
+    pub seats: Vec<PkPassSeat>,
+}
+
+impl PkPassEventSemantics {
+    /// Keys of the `semantics` dictionary that are read.
+    const KEYS: [&'static str; 6] = [
+        "eventName",
+        "venueName",
+        "venueEntrance",
+        "eventStartDate",
+        "eventEndDate",
+        "seats",
+    ];
+
+    fn extract(json: &serde_json::Map<String, serde_json::Value>) -> Result<Self, PassError> {
+        // Semantics are optional, older tickets only have fields.
+        let Some(semantics) = json.get("semantics") else {
+            return Ok(Self::default());
+        };
+        let obj = expect_object(semantics, "semantics")?;
+
+        let string = |key: &str| obj.get(key).and_then(|v| v.as_str()).map(|s| s.to_owned());
+        let seats = match obj.get("seats").and_then(|v| v.as_array()) {
+            Some(seats) => seats.iter()
+                .enumerate()
+                .map(|(idx, seat)| PkPassSeat::extract(seat, &format!("semantics.seats[{idx}]")))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => vec![],
+        };
+
+        Ok(Self {
+            event_name: string("eventName"),
+            venue_name: string("venueName"),
+            venue_entrance: string("venueEntrance"),
+            event_start_date: string("eventStartDate"),
+            event_end_date: string("eventEndDate"),
+            seats,
+        })
+    }
+
+    /// Builds the `semantics` dictionary, `None` if there is nothing to put in it.
+    fn to_json(&self) -> Option<serde_json::Value> {
+        let mut obj = serde_json::Map::new();
+        let entries = [
+            ("eventName", &self.event_name),
+            ("venueName", &self.venue_name),
+            ("venueEntrance", &self.venue_entrance),
+            ("eventStartDate", &self.event_start_date),
+            ("eventEndDate", &self.event_end_date),
+        ];
+        for (key, value) in entries {
+            if let Some(value) = value {
+                obj.insert(key.to_owned(), value.clone().into());
+            }
+        }
+        if !self.seats.is_empty() {
+            obj.insert("seats".to_owned(), self.seats.iter().map(PkPassSeat::to_json).collect());
+        }
+        if obj.is_empty() {
+            return None;
+        }
+        Some(obj.into())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PkPassEventTicket {
+    fields: PkPassFields,
+    semantics: PkPassEventSemantics,
+}
+
+impl PkPassEventTicket {
+    fn extract(json: &serde_json::Map<String, serde_json::Value>, internal_json: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(internal_json, path)?;
+
+        Ok(Self {
+            fields: PkPassFields::extract(obj, path)?,
+            semantics: PkPassEventSemantics::extract(json)?,
+        })
+    }
+
+    /// Builds an event ticket from the neutral event details, adding every detail the accessors
+    /// below look for unless `fields` already has it.
+    fn from_details(event: &EventDetails, mut fields: PkPassFields) -> Self {
+        PkPassValue::insert_missing(&mut fields.primary_fields, "event", "EVENT", event.event_name.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "venue", "VENUE", event.venue_name.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "date", "DATE", event.start.map(|d| d.local.format("%b %-d, %Y %H:%M").to_string()));
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "gate", "GATE", event.gate.clone());
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "section", "SECTION", event.section.clone());
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "row", "ROW", event.row.clone());
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "seat", "SEAT", event.seat.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "holder", "TICKET HOLDER", event.ticket_holder.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "ticket", "TICKET", event.ticket_number.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "address", "ADDRESS", event.venue_address.clone());
+
+        let has_seat = event.section.is_some() || event.row.is_some() || event.seat.is_some();
+        let seats = has_seat.then(|| PkPassSeat {
+            section: event.section.clone(),
+            row: event.row.clone(),
+            number: event.seat.clone(),
+            ..PkPassSeat::default()
+        }).into_iter().collect();
+
+        Self {
+            fields,
+            semantics: PkPassEventSemantics {
+                event_name: event.event_name.clone(),
+                venue_name: event.venue_name.clone(),
+                venue_entrance: event.gate.clone(),
+                // Semantic dates need an offset. Local times still show in the date field.
+                event_start_date: event.start.and_then(|d| d.to_fixed()).map(|d| d.to_rfc3339()),
+                event_end_date: event.end.and_then(|d| d.to_fixed()).map(|d| d.to_rfc3339()),
+                seats,
+            },
+        }
+    }
+
+    /// First seat of the ticket. Tickets for several seats usually come as several passes.
+    fn first_seat(&self) -> Option<&PkPassSeat> {
+        self.semantics.seats.first()
+    }
+
+    pub fn event_name(&self) -> Option<&str> {
+        self.semantics.event_name.as_deref().or_else(|| self.fields.value_for_key("event"))
+    }
+
+    pub fn venue(&self) -> Option<&str> {
+        self.semantics.venue_name.as_deref().or_else(|| self.fields.value_for_key("venue"))
+    }
+
+    pub fn venue_address(&self) -> Option<&str> {
+        self.fields.value_for_key("address")
+    }
+
+    pub fn gate(&self) -> Option<&str> {
+        self.semantics.venue_entrance.as_deref().or_else(|| self.fields.value_for_key("gate"))
+    }
+
+    pub fn section(&self) -> Option<&str> {
+        self.first_seat().and_then(|s| s.section.as_deref()).or_else(|| self.fields.value_for_key("section"))
+    }
+
+    pub fn row(&self) -> Option<&str> {
+        self.first_seat().and_then(|s| s.row.as_deref()).or_else(|| self.fields.value_for_key("row"))
+    }
+
+    pub fn seat(&self) -> Option<&str> {
+        self.first_seat().and_then(|s| s.number.as_deref()).or_else(|| self.fields.value_for_key("seat"))
+    }
+
+    pub fn ticket_holder(&self) -> Option<&str> {
+        self.fields.value_for_key("holder")
+    }
+
+    pub fn ticket_number(&self) -> Option<&str> {
+        self.fields.value_for_key("ticket")
+    }
+
+    fn event_details(&self, report: &mut ConversionReport) -> EventDetails {
+        EventDetails {
+            event_name: self.event_name().map(|s| s.to_owned()),
+            venue_name: self.venue().map(|s| s.to_owned()),
+            venue_address: self.venue_address().map(|s| s.to_owned()),
+            start: report.parse_or_drop("semantics.eventStartDate", self.semantics.event_start_date.as_deref(), LocalDateTime::parse),
+            end: report.parse_or_drop("semantics.eventEndDate", self.semantics.event_end_date.as_deref(), LocalDateTime::parse),
+            gate: self.gate().map(|s| s.to_owned()),
+            section: self.section().map(|s| s.to_owned()),
+            row: self.row().map(|s| s.to_owned()),
+            seat: self.seat().map(|s| s.to_owned()),
+            ticket_holder: self.ticket_holder().map(|s| s.to_owned()),
+            ticket_number: self.ticket_number().map(|s| s.to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PkPassCoupon {
+    fields: PkPassFields,
+}
+
+impl PkPassCoupon {
+    /// Back field keys retailers use for the terms of an offer.
+    const FINE_PRINT_KEYS: [&'static str; 4] = ["terms", "finePrint", "fine_print", "disclaimer"];
+
+    fn extract(internal_json: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(internal_json, path)?;
+
+        Ok(Self {
+            fields: PkPassFields::extract(obj, path)?,
+        })
+    }
+
+    /// Builds a coupon from the neutral coupon details, adding every detail the accessors below
+    /// look for unless `fields` already has it.
+    fn from_details(coupon: &CouponDetails, mut fields: PkPassFields) -> Self {
+        PkPassValue::insert_missing(&mut fields.primary_fields, "offer", "OFFER", coupon.title.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "details", "DETAILS", coupon.details.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "provider", "PROVIDED BY", coupon.provider.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "terms", "TERMS AND CONDITIONS", coupon.fine_print.clone());
+
+        Self {
+            fields,
+        }
+    }
+
+    /// The offer itself, e.g. `20% off`. Coupons lead with it as their first primary field.
+    pub fn offer(&self) -> Option<&str> {
+        PkPassValue::scan_for_value_for_key("offer", self.fields.primary_fields.iter())
+            .or_else(|| self.fields.primary_fields.first().map(|v| v.value.as_str()))
+    }
+
+    pub fn details(&self) -> Option<&str> {
+        self.fields.value_for_key("details")
+    }
+
+    pub fn provider(&self) -> Option<&str> {
+        self.fields.value_for_key("provider")
+    }
+
+    pub fn fine_print(&self) -> Option<&str> {
+        Self::FINE_PRINT_KEYS.into_iter()
+            .find_map(|key| PkPassValue::scan_for_value_for_key(key, self.fields.back_fields.iter()))
+    }
+
+    fn coupon_details(&self) -> CouponDetails {
+        CouponDetails {
+            title: self.offer().map(|s| s.to_owned()),
+            provider: self.provider().map(|s| s.to_owned()),
+            details: self.details().map(|s| s.to_owned()),
+            fine_print: self.fine_print().map(|s| s.to_owned()),
+        }
+    }
+}
+
+/// The `balance` semantic tag.
+#[derive(Debug, Clone)]
+pub struct PkPassCurrencyAmount {
+    pub amount: String,
+    pub currency_code: String,
+}
+
+impl PkPassCurrencyAmount {
+    /// Keys of a currency amount dictionary that are read.
+    const KEYS: [&'static str; 2] = ["amount", "currencyCode"];
+
+    fn extract(value: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(value, path)?;
+
+        let amount = required_str(obj, path, "amount")?;
+        let currency_code = required_str(obj, path, "currencyCode")?;
+
+        Ok(Self {
+            amount,
+            currency_code,
+        })
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut obj = serde_json::Map::new();
+        obj.insert("amount".to_owned(), self.amount.clone().into());
+        obj.insert("currencyCode".to_owned(), self.currency_code.clone().into());
+        obj.into()
+    }
+}
+
+/// The store card semantic tags from the top level `semantics` dictionary.
+#[derive(Debug, Clone, Default)]
+pub struct PkPassStoreCardSemantics {
+    pub membership_program_name: Option<String>,
+    pub membership_program_number: Option<String>,
+    pub balance: Option<PkPassCurrencyAmount>,
+}
+
+impl PkPassStoreCardSemantics {
+    /// Keys of the `semantics` dictionary that are read.
+    const KEYS: [&'static str; 3] = ["membershipProgramName", "membershipProgramNumber", "balance"];
+
+    fn extract(json: &serde_json::Map<String, serde_json::Value>) -> Result<Self, PassError> {
+        let Some(semantics) = json.get("semantics") else {
+            return Ok(Self::default());
+        };
+        let obj = expect_object(semantics, "semantics")?;
+
+        let string = |key: &str| obj.get(key).and_then(|v| v.as_str()).map(|s| s.to_owned());
+        let balance = match obj.get("balance") {
+            Some(balance) => Some(PkPassCurrencyAmount::extract(balance, "semantics.balance")?),
+            None => None,
+        };
+
+        Ok(Self {
+            membership_program_name: string("membershipProgramName"),
+            membership_program_number: string("membershipProgramNumber"),
+            balance,
+        })
+    }
+
+    /// Builds the `semantics` dictionary, `None` if there is nothing to put in it.
+    fn to_json(&self) -> Option<serde_json::Value> {
+        let mut obj = serde_json::Map::new();
+        if let Some(name) = &self.membership_program_name {
+            obj.insert("membershipProgramName".to_owned(), name.clone().into());
+        }
+        if let Some(number) = &self.membership_program_number {
+            obj.insert("membershipProgramNumber".to_owned(), number.clone().into());
+        }
+        if let Some(balance) = &self.balance {
+            obj.insert("balance".to_owned(), balance.to_json());
+        }
+        if obj.is_empty() {
+            return None;
+        }
+        Some(obj.into())
+    }
+}
+
+/// A `storeCard` pass, which covers both loyalty cards and gift cards.
+#[derive(Debug, Clone)]
+pub struct PkPassStoreCard {
+    fields: PkPassFields,
+    semantics: PkPassStoreCardSemantics,
+}
+
+impl PkPassStoreCard {
+    /// Field keys that only show up on gift cards.
+    const GIFT_CARD_KEYS: [&'static str; 3] = ["pin", "cardNumber", "giftCardNumber"];
+    const MEMBER_KEYS: [&'static str; 4] = ["member", "memberId", "memberNumber", "accountNumber"];
+    const CARD_NUMBER_KEYS: [&'static str; 3] = ["cardNumber", "giftCardNumber", "number"];
+    const POINTS_KEYS: [&'static str; 3] = ["points", "balance", "rewards"];
+
+    fn extract(json: &serde_json::Map<String, serde_json::Value>, internal_json: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(internal_json, path)?;
+
+        Ok(Self {
+            fields: PkPassFields::extract(obj, path)?,
+            semantics: PkPassStoreCardSemantics::extract(json)?,
+        })
+    }
+
+    /// Builds a store card from the neutral loyalty details, adding every detail the accessors
+    /// below look for unless `fields` already has it.
+    fn from_loyalty(loyalty: &LoyaltyDetails, mut fields: PkPassFields) -> Self {
+        let points_label = loyalty.points_label.as_deref().unwrap_or("POINTS");
+        PkPassValue::insert_missing(&mut fields.primary_fields, "points", points_label, loyalty.points_balance.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "name", "MEMBER", loyalty.account_name.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "memberId", "MEMBER ID", loyalty.account_id.clone());
+
+        Self {
+            fields,
+            semantics: PkPassStoreCardSemantics {
+                membership_program_name: loyalty.program_name.clone(),
+                membership_program_number: loyalty.account_id.clone(),
+                balance: None,
+            },
+        }
+    }
+
+    /// Builds a store card from the neutral gift card details, adding every detail the accessors
+    /// below look for unless `fields` already has it.
+    fn from_gift_card(gift_card: &GiftCardDetails, mut fields: PkPassFields) -> Self {
+        if let Some(balance) = &gift_card.balance {
+            if !fields.primary_fields.iter().any(|v| v.key == "balance") {
+                fields.primary_fields.push(PkPassValue {
+                    key: "balance".to_owned(),
+                    label: Some("BALANCE".to_owned()),
+                    value: balance.amount.clone(),
+                    currency_code: Some(balance.currency_code.clone()),
+                });
+            }
+        }
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "cardNumber", "CARD NUMBER", gift_card.card_number.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "pin", "PIN", gift_card.pin.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "merchant", "MERCHANT", gift_card.merchant_name.clone());
+
+        Self {
+            fields,
+            semantics: PkPassStoreCardSemantics {
+                membership_program_name: None,
+                membership_program_number: None,
+                balance: gift_card.balance.as_ref().map(|b| PkPassCurrencyAmount {
+                    amount: b.amount.clone(),
+                    currency_code: b.currency_code.clone(),
+                }),
+            },
+        }
+    }
+
+    fn first_value_for_keys(&self, keys: &[&str]) -> Option<&str> {
+        keys.iter().find_map(|key| self.fields.value_for_key(key))
+    }
+
+    /// Money on the card marks it as a gift card, as do gift card only keys like a PIN. Anything
+    /// else is treated as a loyalty card.
+    pub fn is_gift_card(&self) -> bool {
+        self.semantics.balance.is_some()
+            || self.fields.iter().any(|v| v.currency_code.is_some() || Self::GIFT_CARD_KEYS.contains(&v.key.as_str()))
+    }
+
+    pub fn program_name(&self) -> Option<&str> {
+        self.semantics.membership_program_name.as_deref().or_else(|| self.fields.value_for_key("program"))
+    }
+
+    pub fn member_id(&self) -> Option<&str> {
+        self.semantics.membership_program_number.as_deref().or_else(|| self.first_value_for_keys(&Self::MEMBER_KEYS))
+    }
+
+    pub fn member_name(&self) -> Option<&str> {
+        self.first_value_for_keys(&["name", "memberName"])
+    }
+
+    /// Points balance and its label, skipping amounts of money.
+    pub fn points(&self) -> Option<&PkPassValue> {
+        Self::POINTS_KEYS.into_iter()
+            .find_map(|key| self.fields.iter().find(|v| v.key == key && v.currency_code.is_none()))
+    }
+
+    pub fn card_number(&self) -> Option<&str> {
+        self.first_value_for_keys(&Self::CARD_NUMBER_KEYS)
+            .or(self.semantics.membership_program_number.as_deref())
+    }
+
+    pub fn pin(&self) -> Option<&str> {
+        self.fields.value_for_key("pin")
+    }
+
+    pub fn merchant(&self) -> Option<&str> {
+        self.fields.value_for_key("merchant")
+    }
+
+    /// Balance from the semantic tag, or else the first field with a currency.
+    pub fn balance(&self) -> Option<PassMoney> {
+        if let Some(balance) = &self.semantics.balance {
+            return Some(PassMoney {
+                amount: balance.amount.clone(),
+                currency_code: balance.currency_code.clone(),
+            });
+        }
+        self.fields.iter()
+            .find_map(|v| Some(PassMoney {
+                amount: v.value.clone(),
+                currency_code: v.currency_code.clone()?,
+            }))
+    }
+
+    fn loyalty_details(&self) -> LoyaltyDetails {
+        let points = self.points();
+        LoyaltyDetails {
+            program_name: self.program_name().map(|s| s.to_owned()),
+            account_id: self.member_id().map(|s| s.to_owned()),
+            account_name: self.member_name().map(|s| s.to_owned()),
+            points_label: points.and_then(|v| v.label.clone()),
+            points_balance: points.map(|v| v.value.clone()),
+        }
+    }
+
+    fn gift_card_details(&self) -> GiftCardDetails {
+        GiftCardDetails {
+            merchant_name: self.merchant().map(|s| s.to_owned()),
+            card_number: self.card_number().map(|s| s.to_owned()),
+            pin: self.pin().map(|s| s.to_owned()),
+            balance: self.balance(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PkTransitType {
+    Air,
+    Train,
+    Bus,
+    Boat,
+    Generic,
+}
+
+impl PkTransitType {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "PKTransitTypeAir" => Some(Self::Air),
+            "PKTransitTypeTrain" => Some(Self::Train),
+            "PKTransitTypeBus" => Some(Self::Bus),
+            "PKTransitTypeBoat" => Some(Self::Boat),
+            "PKTransitTypeGeneric" => Some(Self::Generic),
+            _ => None,
+        }
+    }
+
+    /// Reads the required `transitType` of the `boardingPass` dictionary at `path`.
+    fn extract(obj: &serde_json::Map<String, serde_json::Value>, path: &str) -> Result<Self, PassError> {
+        let transit_type = required_str(obj, path, "transitType")?;
+        Self::from_str(&transit_type).ok_or_else(|| PassError::InvalidValue {
+            path: json_path(path, "transitType"),
+            value: transit_type,
+        })
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Air => "PKTransitTypeAir",
+            Self::Train => "PKTransitTypeTrain",
+            Self::Bus => "PKTransitTypeBus",
+            Self::Boat => "PKTransitTypeBoat",
+            Self::Generic => "PKTransitTypeGeneric",
+        }
+    }
+}
+
+/// A `boardingPass` for anything but a flight.
+#[derive(Debug, Clone)]
+pub struct PkPassTransit {
+    fields: PkPassFields,
+    transit_type: PkTransitType,
+}
+
+impl PkPassTransit {
+    const ORIGIN_KEYS: [&'static str; 4] = ["origin", "from", "departureStation", "boardPoint"];
+    const DESTINATION_KEYS: [&'static str; 4] = ["destination", "to", "arrivalStation", "offPoint"];
+    const DEPARTURE_KEYS: [&'static str; 3] = ["departure", "departureDate", "departureTime"];
+    const ARRIVAL_KEYS: [&'static str; 3] = ["arrival", "arrivalDate", "arrivalTime"];
+
+    fn extract(internal_json: &serde_json::Value, path: &str) -> Result<Self, PassError> {
+        let obj = expect_object(internal_json, path)?;
+
+        Ok(Self {
+            fields: PkPassFields::extract(obj, path)?,
+            transit_type: PkTransitType::extract(obj, path)?,
+        })
+    }
+
+    /// Builds a transit pass from the neutral transit details, adding every detail the accessors
+    /// below look for unless `fields` already has it.
+    fn from_details(transit: &TransitDetails, mut fields: PkPassFields) -> Self {
+        let transit_type = match transit.mode {
+            TransitMode::Train => PkTransitType::Train,
+            TransitMode::Bus => PkTransitType::Bus,
+            TransitMode::Boat => PkTransitType::Boat,
+            TransitMode::Generic => PkTransitType::Generic,
+        };
+
+        // Station names go in the labels, above the codes, like on a boarding pass.
+        let origin_label = transit.origin_name.as_deref().unwrap_or("FROM");
+        let destination_label = transit.destination_name.as_deref().unwrap_or("TO");
+        PkPassValue::insert_missing(&mut fields.header_fields, "platform", "PLATFORM", transit.platform.clone());
+        PkPassValue::insert_missing(&mut fields.primary_fields, "origin", origin_label, transit.origin.clone().or_else(|| transit.origin_name.clone()));
+        PkPassValue::insert_missing(&mut fields.primary_fields, "destination", destination_label, transit.destination.clone().or_else(|| transit.destination_name.clone()));
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "passenger", "PASSENGER", transit.passenger_name.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "route", "ROUTE", transit.route.clone());
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "departure", "DEPARTS", transit.departure.map(|d| d.to_iso8601()));
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "coach", "COACH", transit.coach.clone());
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "carriage", "CAR", transit.carriage.clone());
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "seat", "SEAT", transit.seat.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "arrival", "ARRIVES", transit.arrival.map(|d| d.to_iso8601()));
+        PkPassValue::insert_missing(&mut fields.back_fields, "ticket", "TICKET", transit.ticket_number.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "operator", "OPERATOR", transit.operator_name.clone());
+
+        Self {
+            fields,
+            transit_type,
+        }
+    }
+
+    fn first_field_for_keys(&self, keys: &[&str]) -> Option<&PkPassValue> {
+        keys.iter().find_map(|key| self.fields.iter().find(|v| v.key == *key))
+    }
+
+    fn first_value_for_keys(&self, keys: &[&str]) -> Option<&str> {
+        self.first_field_for_keys(keys).map(|v| v.value.as_str())
+    }
+
+    pub fn origin(&self) -> Option<&str> {
+        self.first_value_for_keys(&Self::ORIGIN_KEYS)
+    }
+
+    /// Station name, which transit passes put in the label of the origin field.
+    pub fn origin_name(&self) -> Option<&str> {
+        self.first_field_for_keys(&Self::ORIGIN_KEYS).and_then(|v| v.label.as_deref())
+    }
+
+    pub fn destination(&self) -> Option<&str> {
+        self.first_value_for_keys(&Self::DESTINATION_KEYS)
+    }
+
+    pub fn destination_name(&self) -> Option<&str> {
+        self.first_field_for_keys(&Self::DESTINATION_KEYS).and_then(|v| v.label.as_deref())
+    }
 
-impl PassSubtypeOwner for PkPassSubtype {
-    fn check_for_key(key: SubtypeKey, json: &serde_json::Map<String, serde_json::Value>) -> Option<&'static str> {
-        match key {
-            SubtypeKey::Flight => {
-                const KEY: &'static str = "boardingPass";
-                const SECONDARY_KEY: &'static str = "transitType";
-                const AIR_TYPE: &'static str = "PKTransitTypeAir";
-                let Some(map) = json.get(KEY).and_then(|j| j.as_object()) else {
-                    return None;
-                };
-                let Some(transit_type) = map.get(SECONDARY_KEY) else {
-                    return None;
-                };
-                if transit_type.as_str() != Some(AIR_TYPE) {
-                    return None;
-                };
-                Some(KEY)
-            },
-        }
+    pub fn platform(&self) -> Option<&str> {
+        self.first_value_for_keys(&["platform", "track"])
     }
-}
 
-impl PkPassSubtype {
-    fn extract(json: &serde_json::Value, year: i32) -> Result<Self, anyhow::Error> {
-        let Some(obj) = json.as_object() else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "Expected json to be object.".to_owned()).into());
-        };
+    pub fn coach(&self) -> Option<&str> {
+        self.fields.value_for_key("coach")
+    }
 
-        let (key, v) = match Self::extract_subtype(&json) {
-            Ok(subtype_data) => subtype_data,
-            Err(e) => {
-              return Err(std::io::Error::new(std::io::ErrorKind::Other, e.to_owned()).into());
-            },
-        };
+    pub fn carriage(&self) -> Option<&str> {
+        self.first_value_for_keys(&["carriage", "car"])
+    }
 
-        let subtype = match key {
-            SubtypeKey::Flight => {
-                PkPassSubtype::Flight(PkPassFlight::extract(obj, v, year)?)
-            },
-        };
+    pub fn route(&self) -> Option<&str> {
+        self.first_value_for_keys(&["route", "line"])
+    }
 
-        Ok(subtype)
+    pub fn seat(&self) -> Option<&str> {
+        self.fields.value_for_key("seat")
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct PkPassValue {
-    pub key: String,
-    pub label: String,
-    pub value: String,
-}
+    pub fn passenger(&self) -> Option<&str> {
+        self.first_value_for_keys(&["passenger", "name"])
+    }
 
-impl PkPassValue {
-    fn extract_array(value: &Vec<serde_json::Value>) -> Result<Vec<Self>, anyhow::Error> {
-        value.iter().map(|v| Self::extract(v)).collect::<Result<Vec<_>, _>>()
+    pub fn ticket(&self) -> Option<&str> {
+        self.first_value_for_keys(&["ticket", "ticketNumber"])
     }
 
-    fn extract(value: &serde_json::Value) -> Result<Self, anyhow::Error> {
-        let Some(obj) = value.as_object() else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassValue is not an object".to_owned()).into());
-        };
+    pub fn operator(&self) -> Option<&str> {
+        self.fields.value_for_key("operator")
+    }
 
-        let Some(key) = obj.get("key").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassValue is missing key".to_owned()).into());
-        };
-        let Some(label) = obj.get("label").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassValue is missing key".to_owned()).into());
-        };
-        let Some(value) = obj.get("value").and_then(|v| v.as_str()).map(|s| s.to_owned()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassValue is missing key".to_owned()).into());
-        };
+    /// Departure, for passes that give it as a full date field.
+    pub fn parsed_departure_datetime(&self) -> Option<LocalDateTime> {
+        LocalDateTime::parse(self.first_value_for_keys(&Self::DEPARTURE_KEYS)?)
+    }
 
-        Ok(Self {
-          key,
-          label,
-          value,
-        })
+    pub fn parsed_arrival_datetime(&self) -> Option<LocalDateTime> {
+        LocalDateTime::parse(self.first_value_for_keys(&Self::ARRIVAL_KEYS)?)
     }
 
-    fn scan_for_value_for_key<'a>(key: &str, iter: impl Iterator<Item=&'a Self>) -> Option<&'a str> {
-        for entry in iter {
-            if entry.key == key {
-                return Some(entry.value.as_str());
-            }
+    fn transit_details(&self) -> TransitDetails {
+        let mode = match self.transit_type {
+            PkTransitType::Train => TransitMode::Train,
+            PkTransitType::Bus => TransitMode::Bus,
+            PkTransitType::Boat => TransitMode::Boat,
+            PkTransitType::Air | PkTransitType::Generic => TransitMode::Generic,
+        };
+        TransitDetails {
+            mode,
+            operator_name: self.operator().map(|s| s.to_owned()),
+            route: self.route().map(|s| s.to_owned()),
+            origin: self.origin().map(|s| s.to_owned()),
+            origin_name: self.origin_name().map(|s| s.to_owned()),
+            destination: self.destination().map(|s| s.to_owned()),
+            destination_name: self.destination_name().map(|s| s.to_owned()),
+            departure: self.parsed_departure_datetime(),
+            arrival: self.parsed_arrival_datetime(),
+            platform: self.platform().map(|s| s.to_owned()),
+            carriage: self.carriage().map(|s| s.to_owned()),
+            coach: self.coach().map(|s| s.to_owned()),
+            seat: self.seat().map(|s| s.to_owned()),
+            passenger_name: self.passenger().map(|s| s.to_owned()),
+            ticket_number: self.ticket().map(|s| s.to_owned()),
         }
-        None
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum PkTransitType {
-    Air,
+/// A pass.json field of a boarding pass that says something else than its barcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BcbpMismatch {
+    pub key: &'static str,
+    pub pass: String,
+    pub barcode: String,
 }
 
-impl PkTransitType {
-    fn from_str(s: &str) -> Result<Self, anyhow::Error> {
-        match s {
-            "PKTransitTypeAir" => Ok(Self::Air),
-            _ => Err(std::io::Error::new(std::io::ErrorKind::Other, "Not a PkTransitType".to_owned()).into()),
-        }
+impl std::fmt::Display for BcbpMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is {:?} in the pass but {:?} in the barcode", self.key, self.pass, self.barcode)
     }
 }
 
+fn non_empty(s: &str) -> Option<&str> {
+    (!s.is_empty()).then_some(s)
+}
+
 #[derive(Debug, Clone)]
 pub struct PkPassFlight {
-    auxiliary_fields: Vec<PkPassValue>,
-    back_fields: Vec<PkPassValue>,
-    header_fields: Vec<PkPassValue>,
-    primary_fields: Vec<PkPassValue>,
-    secondary_fields: Vec<PkPassValue>,
+    fields: PkPassFields,
     transit_type: PkTransitType,
 
     year: i32,
+    year_source: YearSource,
+    /// The boarding pass barcode, when it holds a BCBP message.
+    bcbp: Option<bcbp::Bcbp>,
 }
 
 impl PkPassFlight {
-    fn extract(json: &serde_json::Map<String, serde_json::Value>, internal_json: &serde_json::Value, year: i32) -> Result<Self, anyhow::Error> {
-        let Some(obj) = internal_json.as_object() else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassValue is not an object".to_owned()).into());
-        };
+    /// Keys of the `boardingPass` dictionary that are read.
+    const KEYS: [&'static str; 6] = [
+        "auxiliaryFields",
+        "backFields",
+        "headerFields",
+        "primaryFields",
+        "secondaryFields",
+        "transitType",
+    ];
 
-        let Some(auxiliary_fields) = obj.get("auxiliaryFields").and_then(|v| v.as_array()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassValue is missing key".to_owned()).into());
-        };
-        let Some(back_fields) = obj.get("backFields").and_then(|v| v.as_array()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassFlight is missing key".to_owned()).into());
-        };
-        let Some(header_fields) = obj.get("headerFields").and_then(|v| v.as_array()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassFlight is missing key".to_owned()).into());
-        };
-        let Some(primary_fields) = obj.get("primaryFields").and_then(|v| v.as_array()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassFlight is missing key".to_owned()).into());
-        };
-        let Some(secondary_fields) = obj.get("secondaryFields").and_then(|v| v.as_array()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassFlight is missing key".to_owned()).into());
-        };
-        let Some(transit_type) = obj.get("transitType").and_then(|v| v.as_str()) else {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "PkPassFlight is missing key".to_owned()).into());
+    fn extract(internal_json: &serde_json::Value, path: &str, year_hints: &flight_year::YearHints, bcbp: Option<bcbp::Bcbp>) -> Result<Self, PassError> {
+        let obj = expect_object(internal_json, path)?;
+
+        let mut flight = Self {
+            fields: PkPassFields::extract(obj, path)?,
+            transit_type: PkTransitType::extract(obj, path)?,
+            year: 0,
+            year_source: YearSource::CurrentDate,
+            bcbp,
         };
+        (flight.year, flight.year_source) = year_hints.infer(flight.date());
 
-        // if (this.departureDateTime === null) {
-        //     return Err(std::io::Error::new(std::io::ErrorKind::Other, "Could not determine flight departure date/time, please specify via hints.json".to_owned()).into());
-        // }
-        Ok(Self {
-            auxiliary_fields: PkPassValue::extract_array(auxiliary_fields)?,
-            back_fields: PkPassValue::extract_array(back_fields)?,
-            header_fields: PkPassValue::extract_array(header_fields)?,
-            primary_fields: PkPassValue::extract_array(primary_fields)?,
-            secondary_fields: PkPassValue::extract_array(secondary_fields)?,
-            transit_type: PkTransitType::from_str(transit_type)?,
+        Ok(flight)
+    }
+
+    /// Builds a boarding pass from the neutral flight details, adding every detail the accessors
+    /// below look for unless `fields` already has it.
+    fn from_details(flight: &FlightDetails, mut fields: PkPassFields) -> Self {
+        let date = flight.departure.or(flight.boarding).map(|d| d.date()).or(flight.date);
+        let (year, year_source) = match date {
+            Some(date) => (date.year(), YearSource::DepartureDate),
+            None => (chrono::Utc::now().year(), YearSource::CurrentDate),
+        };
+        let designator = flight.carrier.as_ref().map(|carrier| {
+            format!("{carrier}{}", flight.flight_number.as_deref().unwrap_or_default())
+        });
+
+        PkPassValue::insert_missing(&mut fields.header_fields, "seat", "SEAT", flight.seat.clone());
+        PkPassValue::insert_missing(&mut fields.header_fields, "flightNb", "FLIGHT", flight.flight_number.clone());
+        PkPassValue::insert_missing(&mut fields.primary_fields, "boardPoint", "FROM", flight.origin.clone());
+        PkPassValue::insert_missing(&mut fields.primary_fields, "offPoint", "TO", flight.destination.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "passenger", "PASSENGER", flight.passenger_name.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "bookingClass", "CLASS", flight.seat_class.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "status", "STATUS", flight.status.clone());
+        PkPassValue::insert_missing(&mut fields.secondary_fields, "group", "GROUP", flight.boarding_group.clone());
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "Date", "DATE", date.map(|d| d.format("%d %b").to_string().to_uppercase()));
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "boardingTime", "BOARDING", flight.boarding.map(|d| d.local.format("%H:%M").to_string()));
+        PkPassValue::insert_missing(&mut fields.auxiliary_fields, "subsidiaryCarrier", "OPERATED BY", designator);
+        PkPassValue::insert_missing(&mut fields.back_fields, "ticket", "TICKET", flight.ticket_number.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "recloc", "CONFIRMATION", flight.confirmation_code.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "fqtv", "FREQUENT FLYER", flight.frequent_flyer_number.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "seq", "SEQUENCE", flight.sequence_number.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "boardPointName", "DEPARTS FROM", flight.origin_name.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "offPointName", "ARRIVES AT", flight.destination_name.clone());
+        PkPassValue::insert_missing(&mut fields.back_fields, "departureTime", "DEPARTURE", flight.departure.map(|d| d.local.format("%H:%M").to_string()));
+        PkPassValue::insert_missing(&mut fields.back_fields, "arrivalTime", "ARRIVAL", flight.arrival.map(|d| d.local.format("%H:%M").to_string()));
+
+        Self {
+            fields,
+            transit_type: PkTransitType::Air,
             year,
-        })
+            year_source,
+            bcbp: None,
+        }
     }
 
     fn auxiliary_field(&self, key: &str) -> Option<&str> {
-        PkPassValue::scan_for_value_for_key(key, self.auxiliary_fields.iter())
+        PkPassValue::scan_for_value_for_key(key, self.fields.auxiliary_fields.iter())
     }
 
     fn back_field(&self, key: &str) -> Option<&str> {
-        PkPassValue::scan_for_value_for_key(key, self.back_fields.iter())
+        PkPassValue::scan_for_value_for_key(key, self.fields.back_fields.iter())
     }
 
     fn header_field(&self, key: &str) -> Option<&str> {
-        PkPassValue::scan_for_value_for_key(key, self.header_fields.iter())
+        PkPassValue::scan_for_value_for_key(key, self.fields.header_fields.iter())
     }
 
     fn primary_field(&self, key: &str) -> Option<&str> {
-        PkPassValue::scan_for_value_for_key(key, self.primary_fields.iter())
+        PkPassValue::scan_for_value_for_key(key, self.fields.primary_fields.iter())
     }
 
     fn secondary_field(&self, key: &str) -> Option<&str> {
-        PkPassValue::scan_for_value_for_key(key, self.secondary_fields.iter())
+        PkPassValue::scan_for_value_for_key(key, self.fields.secondary_fields.iter())
     }
 
     pub fn date(&self) -> Option<&str> {
         self.auxiliary_field("Date")
     }
 
+    /// Year of [`Self::date`], which leaves it out.
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn year_source(&self) -> YearSource {
+        self.year_source
+    }
+
     pub fn boarding_time(&self) -> Option<&str> {
         self.auxiliary_field("boardingTime")
     }
@@ -377,47 +2302,51 @@ impl PkPassFlight {
     }
 
     pub fn ticket(&self) -> Option<&str> {
-        self.back_field("ticket")
+        self.back_field("ticket").or_else(|| self.leg()?.ticket_number.as_deref())
     }
 
     pub fn recloc(&self) -> Option<&str> {
-        self.back_field("recloc")
+        self.back_field("recloc").or_else(|| non_empty(&self.leg()?.pnr))
     }
 
     pub fn frequent_flyer(&self) -> Option<&str> {
-        self.back_field("fqtv")
+        self.back_field("fqtv").or_else(|| self.leg()?.frequent_flyer_number.as_deref())
     }
 
     pub fn sequence(&self) -> Option<&str> {
-        self.back_field("seq")
+        self.back_field("seq").or_else(|| non_empty(&self.leg()?.sequence))
     }
 
     pub fn departure_time(&self) -> Option<&str> {
         self.back_field("departureTime")
     }
 
+    pub fn arrival_time(&self) -> Option<&str> {
+        self.back_field("arrivalTime")
+    }
+
     pub fn seat(&self) -> Option<&str> {
-        self.header_field("seat")
+        self.header_field("seat").or_else(|| non_empty(&self.leg()?.seat))
     }
 
     pub fn flight_number(&self) -> Option<&str> {
-        self.header_field("flightNb")
+        self.header_field("flightNb").or_else(|| non_empty(&self.leg()?.flight_number))
     }
 
     pub fn board_point(&self) -> Option<&str> {
-        self.primary_field("boardPoint")
+        self.primary_field("boardPoint").or_else(|| non_empty(&self.leg()?.origin))
     }
 
     pub fn off_point(&self) -> Option<&str> {
-        self.primary_field("offPoint")
+        self.primary_field("offPoint").or_else(|| non_empty(&self.leg()?.destination))
     }
 
     pub fn passenger(&self) -> Option<&str> {
-        self.secondary_field("passenger")
+        self.secondary_field("passenger").or_else(|| non_empty(&self.bcbp.as_ref()?.passenger_name))
     }
 
     pub fn booking_class(&self) -> Option<&str> {
-        self.secondary_field("bookingClass")
+        self.secondary_field("bookingClass").or_else(|| non_empty(&self.leg()?.compartment))
     }
 
     pub fn status(&self) -> Option<&str> {
@@ -428,85 +2357,888 @@ impl PkPassFlight {
         self.secondary_field("group")
     }
 
-    pub fn parsed_boarding_datetime(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
-        let offset = chrono::FixedOffset::west_opt(chrono::Duration::hours(7).num_seconds() as i32)?;
-        let time = format!("{} {} {}", self.year, self.date()?, self.boarding_time()?);
-        println!("{:?}", time);
-        chrono::NaiveDateTime::parse_from_str(time.as_str(), "%Y %d %b %H:%M").ok().and_then(|c| c.and_local_timezone(offset).single())
+    /// The BCBP message in the barcode, which the accessors above fall back to when the airline
+    /// uses other keys.
+    pub fn bcbp(&self) -> Option<&bcbp::Bcbp> {
+        self.bcbp.as_ref()
+    }
+
+    /// First leg of the BCBP message, the one the pass is for.
+    fn leg(&self) -> Option<&bcbp::BcbpLeg> {
+        self.bcbp.as_ref()?.legs.first()
+    }
+
+    /// Fields whose pass.json value disagrees with the BCBP message in the barcode.
+    pub fn bcbp_mismatches(&self) -> Vec<BcbpMismatch> {
+        let Some(leg) = self.leg() else {
+            return vec![];
+        };
+        // Passes pad numbers and seats differently than barcodes do.
+        let normalize = |s: &str| s.trim().trim_start_matches('0').to_ascii_uppercase();
+        let checks = [
+            ("seat", self.header_field("seat"), Some(leg.seat.as_str())),
+            ("flightNb", self.header_field("flightNb"), Some(leg.flight_number.as_str())),
+            ("boardPoint", self.primary_field("boardPoint"), Some(leg.origin.as_str())),
+            ("offPoint", self.primary_field("offPoint"), Some(leg.destination.as_str())),
+            ("recloc", self.back_field("recloc"), Some(leg.pnr.as_str())),
+            ("seq", self.back_field("seq"), Some(leg.sequence.as_str())),
+            ("ticket", self.back_field("ticket"), leg.ticket_number.as_deref()),
+            ("fqtv", self.back_field("fqtv"), leg.frequent_flyer_number.as_deref()),
+        ];
+        let mut mismatches: Vec<_> = checks.into_iter()
+            .filter_map(|(key, pass, barcode)| Some((key, pass?, barcode?)))
+            .filter(|(_, pass, barcode)| !barcode.is_empty() && normalize(pass) != normalize(barcode))
+            .map(|(key, pass, barcode)| BcbpMismatch {
+                key,
+                pass: pass.to_owned(),
+                barcode: barcode.to_owned(),
+            })
+            .collect();
+
+        let date = self.date()
+            .and_then(|date| chrono::NaiveDate::parse_from_str(&format!("{} {date}", self.year), "%Y %d %b").ok());
+        if let (Some(date), Some(flight_day)) = (date, leg.flight_day) {
+            if date.ordinal() != flight_day {
+                mismatches.push(BcbpMismatch {
+                    key: "Date",
+                    pass: date.format("%d %b %Y").to_string(),
+                    barcode: format!("day {flight_day}"),
+                });
+            }
+        }
+
+        mismatches
+    }
+
+    fn flight_details(&self, report: &mut ConversionReport) -> FlightDetails {
+        // The subsidiary carrier is the marketing flight designator, e.g. `UA1234`.
+        let designator = self.subsidiary_carrier();
+        let timed_airports = [
+            self.board_point().filter(|_| self.boarding_time().is_some() || self.departure_time().is_some()),
+            self.off_point().filter(|_| self.arrival_time().is_some()),
+        ];
+        for code in timed_airports.into_iter().flatten() {
+            if airport::lookup(code).is_none() {
+                report.warn(ConversionWarning::UnknownAirport(code.to_owned()));
+            }
+        }
+        FlightDetails {
+            carrier: designator.and_then(|s| s.get(..2)).or_else(|| non_empty(&self.leg()?.operating_carrier)).map(|s| s.to_owned()),
+            flight_number: self.flight_number().or_else(|| designator.and_then(|s| s.get(2..))).map(|s| s.to_owned()),
+            origin: self.board_point().map(|s| s.to_owned()),
+            destination: self.off_point().map(|s| s.to_owned()),
+            origin_name: None,
+            destination_name: None,
+            origin_terminal: None,
+            origin_gate: None,
+            date: None,
+            boarding: self.parsed_boarding_datetime(),
+            departure: self.parsed_departure_datetime(),
+            arrival: self.parsed_arrival_datetime(),
+            passenger_name: self.passenger().map(|s| s.to_owned()),
+            seat: self.seat().map(|s| s.to_owned()),
+            seat_class: self.booking_class().map(|s| s.to_owned()),
+            boarding_group: self.group().map(|s| s.to_owned()),
+            sequence_number: self.sequence().map(|s| s.to_owned()),
+            status: self.status().map(|s| s.to_owned()),
+            confirmation_code: self.recloc().map(|s| s.to_owned()),
+            ticket_number: self.ticket().map(|s| s.to_owned()),
+            frequent_flyer_number: self.frequent_flyer().map(|s| s.to_owned()),
+        }
+    }
+
+    /// `time` on the pass's date, in the time zone of the airport `code`. Unknown airports keep
+    /// the time as the pass shows it, without a guessed offset.
+    fn local_datetime(&self, code: &str, time: &str) -> Option<LocalDateTime> {
+        let time = format!("{} {} {}", self.year, self.date()?, time);
+        let time = chrono::NaiveDateTime::parse_from_str(time.as_str(), "%Y %d %b %H:%M").ok()?;
+        match airport::lookup(code) {
+            Some(airport) => airport.local_datetime(time).map(Into::into),
+            None => Some(LocalDateTime::naive(time)),
+        }
+    }
+
+    pub fn parsed_boarding_datetime(&self) -> Option<LocalDateTime> {
+        self.local_datetime(self.board_point()?, self.boarding_time()?)
+    }
+
+    pub fn parsed_departure_datetime(&self) -> Option<LocalDateTime> {
+        self.local_datetime(self.board_point()?, self.departure_time()?)
     }
 
-    pub fn parsed_departure_datetime(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
-        // TODO Get departure time from airport code. This is PDT.
-        let offset = chrono::FixedOffset::west_opt(chrono::Duration::hours(7).num_seconds() as i32)?;
-        let time = format!("{} {} {}", self.year, self.date()?, self.departure_time()?);
-        chrono::NaiveDateTime::parse_from_str(time.as_str(), "%Y %d %b %H:%M").ok().and_then(|c| c.and_local_timezone(offset).single())
+    /// Arrival at the off point. The pass only dates the departure, so an arrival that would come
+    /// before it lands on a later day.
+    pub fn parsed_arrival_datetime(&self) -> Option<LocalDateTime> {
+        let mut arrival = self.local_datetime(self.off_point()?, self.arrival_time()?)?;
+        if let Some(departure) = self.parsed_departure_datetime() {
+            let before_departure = |arrival: &LocalDateTime| match (arrival.to_fixed(), departure.to_fixed()) {
+                (Some(arrival), Some(departure)) => arrival < departure,
+                // Without both offsets the clocks are all there is to go by.
+                _ => arrival.local < departure.local,
+            };
+            while before_departure(&arrival) {
+                arrival.local += chrono::Duration::days(1);
+            }
+        }
+
+        Some(arrival)
     }
 }
 
 impl PkPass {
-    pub fn read<R: Read + Seek>(pkpass_r: R, year: i32) -> Result<Self, anyhow::Error> {
+    /// Reads a pkpass archive, checking its files against `manifest.json` as `manifest_check` says.
+    /// Boarding passes take `year` as their flight's year, or work it out when it's `None`.
+    pub fn read<R: Read + Seek>(pkpass_r: R, year: Option<i32>, manifest_check: ManifestCheck) -> Result<Self, PassError> {
         let l10n_path_part = ".lproj/";
         let mut pkpass = zip::ZipArchive::new(pkpass_r)?;
 
         let pass_json: serde_json::Value = serde_json::from_reader(pkpass.by_name("pass.json")?)?;
-        println!("JSON\n{pass_json:#?}");
 
         let mut files = HashMap::new();
+        let mut file_date = None;
         for idx in 0..pkpass.len() {
-            let piece = pkpass.by_index(idx)?;
+            let mut piece = pkpass.by_index(idx)?;
+            if piece.is_dir() {
+                continue;
+            }
             let name = piece.name().to_owned();
-            let data = piece.bytes().collect::<Result<Vec<_>, _>>()?;
-
-            println!("Processing {:?}", name);
-
+            // Archives written without timestamps date everything to the start of 1980.
+            let modified = piece.last_modified();
+            if name == "pass.json" && modified.year() > 1980 {
+                file_date = chrono::NaiveDate::from_ymd_opt(modified.year().into(), modified.month().into(), modified.day().into());
+            }
+            let mut data = vec![];
+            piece.read_to_end(&mut data)?;
             files.insert(name, data);
         }
 
-        let mut l10n: HashMap<_, HashMap<_, _>> = HashMap::new();
+        let manifest_issues = ManifestIssue::check(&files);
+        if manifest_check == ManifestCheck::Strict && !manifest_issues.is_empty() {
+            return Err(PassError::ManifestMismatch(manifest_issues));
+        }
+
+        let mut l10n: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+        let mut invalid_strings = BTreeMap::new();
         for (name, data) in files.iter() {
             let Some(suffix_start) = name.find(l10n_path_part) else {
                 continue;
             };
             let suffix_end = suffix_start + l10n_path_part.len();
 
-            let lang = (&name[0..suffix_start]).to_owned();
-            let path = (&name[suffix_end..]).to_owned();
+            let lang = name[0..suffix_start].to_owned();
+            let path = &name[suffix_end..];
 
-            l10n.entry(lang).or_default().insert(path, data.clone());
+            if path != "pass.strings" {
+                continue;
+            }
+            // Wallet shows the pass untranslated rather than refusing it.
+            match strings::parse(data) {
+                Ok(strings) => l10n.entry(lang).or_default().extend(strings),
+                Err(message) => {
+                    invalid_strings.insert(name.clone(), message);
+                },
+            }
         }
 
-        let base = PkPassBase::extract(&pass_json, year)?;
-        let subtype = PkPassSubtype::extract(&pass_json, year)?;
-        let barcode = PkPassBarcode::extract(&pass_json, year)?;
+        let manifest = files.get("manifest.json").cloned();
+        let signature = files.get("signature").cloned();
+        let images = files.into_iter()
+            .filter_map(|(name, data)| PassImage::from_pkpass_path(&name, data))
+            .collect();
+
+        let base = PkPassBase::extract(&pass_json)?;
+        let barcodes = PkPassBarcode::extract_all(&pass_json)?;
+        let bcbp = barcodes.iter().find_map(|b| bcbp::parse(&b.message).ok());
+        let year_hints = flight_year::YearHints {
+            year,
+            relevant_date: base.relevant_date.as_deref().and_then(parse_w3c_datetime).map(|d| d.date_naive()),
+            barcode_issue_date: bcbp.as_ref().and_then(|b| b.issue_date),
+            expiration_date: base.expiration_date.as_deref().and_then(parse_w3c_datetime).map(|d| d.date_naive()),
+            file_date,
+            today: chrono::Utc::now().date_naive(),
+        };
+        let subtype = PkPassSubtype::extract(&pass_json, &year_hints, bcbp)?;
 
         Ok(PkPass {
+            json: Some(pass_json),
+            l10n,
+            invalid_strings,
+            locale: None,
+            manifest_issues,
+            manifest,
+            signature,
             base,
             subtype,
-            barcode,
+            barcodes,
+            images,
+        })
+    }
+}
+
+impl PkPass {
+    /// Picks the `.lproj` locale to show the pass in: `locale` or its language if the pass has
+    /// them, else English, else whichever comes first.
+    pub fn resolve_locale(&self, locale: Option<&str>) -> Option<&str> {
+        let normalize = |l: &str| l.replace('_', "-").to_ascii_lowercase();
+        let language = |l: &str| normalize(l).split('-').next().unwrap_or_default().to_owned();
+        let find = |wanted: &dyn Fn(&str) -> bool| self.l10n.keys().find(|l| wanted(l)).map(String::as_str);
+
+        locale.and_then(|locale| {
+            find(&|l| normalize(l) == normalize(locale))
+                .or_else(|| find(&|l| normalize(l) == language(locale)))
+                .or_else(|| find(&|l| language(l) == language(locale)))
         })
+            .or_else(|| find(&|l| language(l) == "en"))
+            .or_else(|| self.l10n.keys().next().map(String::as_str))
+    }
+
+    /// Replaces the text the pass shows with its translation for `locale`, as Wallet would when
+    /// displaying it. Only strings that are keys of the locale's `pass.strings` change.
+    pub fn localize(&mut self, locale: Option<&str>) {
+        let Some(locale) = self.resolve_locale(locale).map(str::to_owned) else {
+            return;
+        };
+        let Some(strings) = self.l10n.get(&locale) else {
+            return;
+        };
+        self.locale = Some(locale.clone());
+
+        let mut texts = vec![&mut self.base.organization_name, &mut self.base.description];
+        texts.extend(self.base.logo_text.as_mut());
+        texts.extend(self.base.locations.iter_mut().filter_map(|l| l.relevant_text.as_mut()));
+        texts.extend(self.barcodes.iter_mut().filter_map(|b| b.alt_text.as_mut()));
+        for field in self.subtype.fields_mut().iter_mut() {
+            texts.extend(field.label.as_mut());
+            texts.push(&mut field.value);
+        }
+        for text in texts {
+            if let Some(translation) = strings.get(text.as_str()) {
+                text.clone_from(translation);
+            }
+        }
+    }
+
+    /// Translations of the localized text into every other locale of [`Self::l10n`].
+    fn localizations(&self) -> PassLocalizations {
+        let Some((locale, strings)) = self.locale.as_ref().and_then(|l| self.l10n.get_key_value(l)) else {
+            return PassLocalizations::default();
+        };
+
+        let mut translations: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+        for (key, text) in strings.iter() {
+            for (other, strings) in self.l10n.iter().filter(|(l, _)| *l != locale) {
+                if let Some(translation) = strings.get(key) {
+                    translations.entry(text.clone()).or_default().entry(language_tag(other)).or_insert_with(|| translation.clone());
+                }
+            }
+        }
+
+        PassLocalizations {
+            language: Some(language_tag(locale)),
+            translations,
+        }
+    }
+
+    /// Builds `pass.json`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut obj = serde_json::Map::new();
+        self.base.to_json(&mut obj);
+        if !self.barcodes.is_empty() {
+            obj.insert("barcodes".to_owned(), self.barcodes.iter().map(PkPassBarcode::to_json).collect());
+        }
+        // Devices older than iOS 9 only read `barcode`, which can't hold a Code128.
+        let legacy = self.barcodes.iter().find(|b| b.format != BarcodeFormat::Code128.as_pkpass_str());
+        if let Some(barcode) = legacy {
+            obj.insert("barcode".to_owned(), barcode.to_json());
+        }
+        self.subtype.to_json(&mut obj);
+        obj.into()
+    }
+
+    /// Writes a pkpass archive holding `pass.json`, its images and `pass.strings` tables, and the
+    /// `manifest.json`, signed by `signer`. Unsigned passes only open in readers that don't check.
+    pub fn write<W: Write>(&self, mut pkpass_w: W, signer: Option<&Signer>) -> Result<(), PassError> {
+        if let Some(signer) = signer {
+            signer.check_identity(&self.base.pass_type_identifier, &self.base.team_identifier)?;
+        }
+
+        let mut files = BTreeMap::new();
+        files.insert("pass.json".to_owned(), serde_json::to_vec_pretty(&self.to_json())?);
+        for (locale, strings) in self.l10n.iter() {
+            files.insert(format!("{locale}.lproj/pass.strings"), strings::to_bytes(strings));
+        }
+        for image in self.images.iter() {
+            files.insert(image.pkpass_path(), image.data.clone());
+        }
+
+        // The manifest lists the SHA-1 of every other file in the archive.
+        let manifest: serde_json::Map<_, _> = files.iter()
+            .map(|(name, data)| (name.clone(), format!("{:x}", Sha1::digest(data)).into()))
+            .collect();
+        let manifest = serde_json::to_vec_pretty(&manifest)?;
+        if let Some(signer) = signer {
+            files.insert("signature".to_owned(), signer.sign(&manifest)?);
+        }
+        files.insert("manifest.json".to_owned(), manifest);
+
+        // Zip needs to seek back to write its directory, which pipes can't do.
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+        for (name, data) in files {
+            archive.start_file(name, zip::write::FileOptions::default())?;
+            archive.write_all(&data)?;
+        }
+        let archive = archive.finish()?.into_inner();
+        pkpass_w.write_all(&archive)?;
+
+        Ok(())
+    }
+
+    /// Key the barcodes were read from.
+    fn barcode_key(&self) -> &'static str {
+        let legacy = self.json.as_ref()
+            .and_then(|j| j.as_object())
+            .is_some_and(|obj| !obj.contains_key("barcodes"));
+        if legacy {
+            "barcode"
+        } else {
+            "barcodes"
+        }
+    }
+
+    /// JSON path of the barcode at `idx`, for reporting.
+    fn barcode_path(&self, idx: usize) -> String {
+        match self.barcode_key() {
+            "barcode" => "barcode".to_owned(),
+            key => format!("{key}[{idx}]"),
+        }
+    }
+
+    /// Walks the original `pass.json` and records every value that was not read.
+    fn report_unread(&self, report: &mut ConversionReport) {
+        let Some(obj) = self.json.as_ref().and_then(|j| j.as_object()) else {
+            return;
+        };
+
+        let subtype_key = self.subtype.json_key();
+        let top_level_keys: Vec<_> = PkPassBase::KEYS.into_iter()
+            .chain([subtype_key])
+            .chain(self.subtype.top_level_keys().iter().copied())
+            .collect();
+        report.drop_unknown_keys("", obj, &top_level_keys);
+
+        if let PkPassSubtype::StoreCard(_) = &self.subtype {
+            if let Some(semantics) = obj.get("semantics").and_then(|v| v.as_object()) {
+                report.drop_unknown_keys("semantics", semantics, &PkPassStoreCardSemantics::KEYS);
+                if let Some(balance) = semantics.get("balance").and_then(|v| v.as_object()) {
+                    report.drop_unknown_keys("semantics.balance", balance, &PkPassCurrencyAmount::KEYS);
+                }
+            }
+        }
+        if let PkPassSubtype::EventTicket(_) = &self.subtype {
+            if let Some(semantics) = obj.get("semantics").and_then(|v| v.as_object()) {
+                report.drop_unknown_keys("semantics", semantics, &PkPassEventSemantics::KEYS);
+                let seats = semantics.get("seats").and_then(|v| v.as_array()).into_iter().flatten();
+                for (idx, seat) in seats.enumerate() {
+                    if let Some(seat) = seat.as_object() {
+                        report.drop_unknown_keys(&format!("semantics.seats[{idx}]"), seat, &PkPassSeat::KEYS);
+                    }
+                }
+            }
+        }
+
+        // The legacy `barcode` is only read without `barcodes`, it is usually a copy of the first.
+        let barcode_key = self.barcode_key();
+        match obj.get(barcode_key) {
+            Some(serde_json::Value::Array(barcodes)) => {
+                for (idx, barcode) in barcodes.iter().enumerate() {
+                    if let Some(barcode) = barcode.as_object() {
+                        report.drop_unknown_keys(&format!("{barcode_key}[{idx}]"), barcode, &PkPassBarcode::KEYS);
+                    }
+                }
+            },
+            Some(serde_json::Value::Object(barcode)) => {
+                report.drop_unknown_keys(barcode_key, barcode, &PkPassBarcode::KEYS);
+            },
+            _ => {},
+        }
+        let locations = obj.get("locations").and_then(|v| v.as_array()).into_iter().flatten();
+        for (idx, location) in locations.enumerate() {
+            if let Some(location) = location.as_object() {
+                report.drop_unknown_keys(&format!("locations[{idx}]"), location, &PkPassLocation::KEYS);
+            }
+        }
+
+        let Some(subtype) = obj.get(subtype_key).and_then(|v| v.as_object()) else {
+            return;
+        };
+        report.drop_unknown_keys(subtype_key, subtype, self.subtype.keys());
+        for group_key in FIELD_GROUP_KEYS {
+            let values = subtype.get(group_key).and_then(|v| v.as_array()).into_iter().flatten();
+            for (idx, value) in values.enumerate() {
+                if let Some(value) = value.as_object() {
+                    report.drop_unknown_keys(&format!("{subtype_key}.{group_key}[{idx}]"), value, self.subtype.field_keys());
+                }
+            }
+        }
+    }
+}
+
+impl Pass {
+    /// Converts a pkpass, reporting every `pass.json` value that has no slot in [`Pass`].
+    pub fn from_pkpass(mut pkpass: PkPass) -> (Self, ConversionReport) {
+        let mut report = ConversionReport::default();
+        pkpass.report_unread(&mut report);
+        for (file, message) in pkpass.invalid_strings.iter() {
+            report.warn(ConversionWarning::InvalidStrings {
+                file: file.clone(),
+                message: message.clone(),
+            });
+        }
+
+        if pkpass.locale.is_none() {
+            pkpass.localize(None);
+        }
+        let localizations = pkpass.localizations();
+
+        let barcode_paths: Vec<_> = (0..pkpass.barcodes.len()).map(|idx| pkpass.barcode_path(idx)).collect();
+        let PkPass { base, barcodes, images, subtype, .. } = pkpass;
+
+        let colors = PassColors {
+            background: report.parse_or_drop("backgroundColor", Some(&base.background_color), PassColor::parse),
+            foreground: report.parse_or_drop("foregroundColor", Some(&base.foreground_color), PassColor::parse),
+            label: report.parse_or_drop("labelColor", base.label_color.as_deref(), PassColor::parse),
+        };
+        let dates = PassDates {
+            relevant: report.parse_or_drop("relevantDate", base.relevant_date.as_deref(), parse_w3c_datetime),
+            expiration: report.parse_or_drop("expirationDate", base.expiration_date.as_deref(), parse_w3c_datetime),
+        };
+        let barcodes = barcodes.into_iter()
+            .zip(barcode_paths)
+            .filter_map(|(barcode, path)| {
+                let format = report.parse_or_drop(&format!("{path}.format"), Some(&barcode.format), BarcodeFormat::from_pkpass_str)?;
+                Some(PassBarcode {
+                    format,
+                    message: barcode.message,
+                    encoding: barcode.encoding,
+                    alt_text: barcode.alt_text,
+                })
+            })
+            .collect();
+        let locations = base.locations.into_iter().map(|l| PassLocation {
+            latitude: l.latitude,
+            longitude: l.longitude,
+            altitude: l.altitude,
+            relevant_text: l.relevant_text,
+        }).collect();
+
+        let (fields, category) = match subtype {
+            PkPassSubtype::Flight(flight) => (flight.fields.to_pass_fields(), PassCategory::Flight(flight.flight_details(&mut report))),
+            PkPassSubtype::Transit(transit) => {
+                let mut details = transit.transit_details();
+                details.departure = details.departure.or(dates.relevant.map(Into::into));
+                (transit.fields.to_pass_fields(), PassCategory::Transit(details))
+            },
+            PkPassSubtype::EventTicket(event) => {
+                let mut details = event.event_details(&mut report);
+                // Tickets without semantics usually only carry the event start as the relevant date.
+                details.start = details.start.or(dates.relevant.map(Into::into));
+                (event.fields.to_pass_fields(), PassCategory::Event(details))
+            },
+            PkPassSubtype::Coupon(coupon) => (coupon.fields.to_pass_fields(), PassCategory::Coupon(coupon.coupon_details())),
+            PkPassSubtype::StoreCard(store_card) => {
+                let category = if store_card.is_gift_card() {
+                    PassCategory::GiftCard(store_card.gift_card_details())
+                } else {
+                    PassCategory::Loyalty(store_card.loyalty_details())
+                };
+                (store_card.fields.to_pass_fields(), category)
+            },
+            PkPassSubtype::Generic(generic) => (generic.fields.to_pass_fields(), PassCategory::Generic),
+        };
+
+        let pass = Pass {
+            serial_number: base.serial_number,
+            type_identifier: base.pass_type_identifier,
+            issuer: PassIssuer {
+                organization_name: base.organization_name,
+                team_identifier: Some(base.team_identifier),
+                google_issuer_id: None,
+            },
+            description: base.description,
+            logo_text: base.logo_text,
+            voided: base.voided,
+            colors,
+            barcodes,
+            fields,
+            dates,
+            locations,
+            images,
+            localizations,
+            category,
+        };
+
+        (pass, report)
     }
 }
 
-impl From<GoogleWalletPass> for Pass {
-    fn from(pass: GoogleWalletPass) -> Self {
-        unimplemented!("googlepass to Pass");
+/// Side of the @1x icon, in points.
+const ICON_SIZE: u32 = 29;
+
+/// Fits the logo into a square icon at the logo's scale, on a transparent background.
+fn icon_from_logo(logo: &PassImage) -> Option<PassImage> {
+    let size = ICON_SIZE * u32::from(logo.scale);
+    let resized = image::load_from_memory(&logo.data).ok()?
+        .resize(size, size, image::imageops::FilterType::Lanczos3)
+        .to_rgba8();
+    let mut icon = image::RgbaImage::new(size, size);
+    image::imageops::overlay(&mut icon, &resized, (size - resized.width()) / 2, (size - resized.height()) / 2);
+    let mut data = vec![];
+    image::DynamicImage::ImageRgba8(icon).write_to(&mut data, image::ImageOutputFormat::Png).ok()?;
+
+    Some(PassImage {
+        kind: PassImageKind::Icon,
+        scale: logo.scale,
+        locale: None,
+        data,
+    })
+}
+
+impl Pass {
+    /// Converts to a pkpass, which Wallet refuses without an icon. Passes without one, as Google
+    /// passes are, get one made from their logo, and fail if they have none either.
+    pub fn to_pkpass(mut self) -> Result<(PkPass, ConversionReport), PassError> {
+        let mut report = ConversionReport::default();
+        if self.image(PassImageKind::Icon).is_none() {
+            let Some(icon) = self.image(PassImageKind::Logo).and_then(icon_from_logo) else {
+                return Err(PassError::MissingIcon);
+            };
+            report.warn(ConversionWarning::IconFromLogo);
+            self.images.push(icon);
+        }
+
+        Ok((PkPass::from(self), report))
+    }
+
+    /// Writes the pass as `format`: a Google Wallet JSON payload with its images linked under
+    /// `image_base_url`, or a pkpass archive signed by `signer` when there is one. Passes that
+    /// don't know their team, as Google passes and barcodes don't, take the team and pass type
+    /// the certificate was issued for. Returns what the conversion reported.
+    pub fn write<W: Write>(self, mut output: W, format: PassFormat, signer: Option<&Signer>, image_base_url: Option<&str>) -> Result<ConversionReport, PassError> {
+        let report = match format {
+            PassFormat::GPass => {
+                let (gpass, report) = self.to_gpass(image_base_url)?;
+                serde_json::to_writer_pretty(&mut output, &gpass)?;
+                writeln!(output)?;
+                report
+            },
+            PassFormat::PkPass => {
+                let (mut pkpass, report) = self.to_pkpass()?;
+                if let Some(signer) = signer {
+                    if pkpass.base.team_identifier.is_empty() {
+                        let (pass_type_identifier, team_identifier) = signer.identity();
+                        pkpass.base.team_identifier = team_identifier.unwrap_or_default();
+                        if let Some(pass_type_identifier) = pass_type_identifier {
+                            pkpass.base.pass_type_identifier = pass_type_identifier;
+                        }
+                    }
+                }
+                for (key, value) in [
+                    ("passTypeIdentifier", &pkpass.base.pass_type_identifier),
+                    ("teamIdentifier", &pkpass.base.team_identifier),
+                    ("organizationName", &pkpass.base.organization_name),
+                ] {
+                    if value.is_empty() {
+                        return Err(PassError::MissingIdentifier(key));
+                    }
+                }
+                pkpass.write(&mut output, signer)?;
+                report
+            },
+        };
+        output.flush()?;
+
+        Ok(report)
     }
 }
 
 impl From<PkPass> for Pass {
     fn from(pass: PkPass) -> Self {
-        unimplemented!("pkpass to Pass");
+        Self::from_pkpass(pass).0
     }
 }
 
-impl From<Pass> for GoogleWalletPass {
+impl From<Pass> for PkPass {
     fn from(pass: Pass) -> Self {
-        unimplemented!("pass to google");
+        let background = pass.colors.background.unwrap_or(PassColor { r: 255, g: 255, b: 255 });
+        // Pick whichever of black and white reads better on the background.
+        let luma = 299 * background.r as u32 + 587 * background.g as u32 + 114 * background.b as u32;
+        let foreground = pass.colors.foreground.unwrap_or(if luma > 128_000 {
+            PassColor { r: 0, g: 0, b: 0 }
+        } else {
+            PassColor { r: 255, g: 255, b: 255 }
+        });
+
+        let base = PkPassBase {
+            serial_number: pass.serial_number,
+            format_version: 1,
+            pass_type_identifier: pass.type_identifier,
+            organization_name: pass.issuer.organization_name,
+            team_identifier: pass.issuer.team_identifier.unwrap_or_default(),
+            description: pass.description,
+            background_color: background.to_rgb_string(),
+            foreground_color: foreground.to_rgb_string(),
+            label_color: pass.colors.label.map(|c| c.to_rgb_string()),
+            logo_text: pass.logo_text,
+            relevant_date: pass.dates.relevant.map(|d| d.to_rfc3339()),
+            expiration_date: pass.dates.expiration.map(|d| d.to_rfc3339()),
+            voided: pass.voided,
+            locations: pass.locations.into_iter().map(|l| PkPassLocation {
+                latitude: l.latitude,
+                longitude: l.longitude,
+                altitude: l.altitude,
+                relevant_text: l.relevant_text,
+            }).collect(),
+        };
+        let barcodes = pass.barcodes.into_iter().map(|b| PkPassBarcode {
+            format: b.format.as_pkpass_str().to_owned(),
+            message: b.message,
+            encoding: b.encoding,
+            alt_text: b.alt_text,
+        }).collect();
+
+        // Text is its own key, with a table for the pass's language so it is picked over the others.
+        let mut l10n: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+        for (text, by_language) in pass.localizations.translations.iter() {
+            for (language, translation) in by_language {
+                l10n.entry(lproj_name(language)).or_default().insert(text.clone(), translation.clone());
+            }
+            if let Some(language) = pass.localizations.language.as_ref() {
+                l10n.entry(lproj_name(language)).or_default().insert(text.clone(), text.clone());
+            }
+        }
+
+        // Without translations there is no table to pick.
+        let locale = pass.localizations.language.as_deref().map(lproj_name).filter(|l| l10n.contains_key(l));
+
+        let fields = PkPassFields::from_pass_fields(&pass.fields);
+        let subtype = match &pass.category {
+            PassCategory::Flight(flight) => PkPassSubtype::Flight(PkPassFlight::from_details(flight, fields)),
+            PassCategory::Transit(transit) => PkPassSubtype::Transit(PkPassTransit::from_details(transit, fields)),
+            PassCategory::Event(event) => PkPassSubtype::EventTicket(PkPassEventTicket::from_details(event, fields)),
+            PassCategory::Coupon(coupon) => PkPassSubtype::Coupon(PkPassCoupon::from_details(coupon, fields)),
+            PassCategory::Loyalty(loyalty) => PkPassSubtype::StoreCard(PkPassStoreCard::from_loyalty(loyalty, fields)),
+            PassCategory::GiftCard(gift_card) => PkPassSubtype::StoreCard(PkPassStoreCard::from_gift_card(gift_card, fields)),
+            // Categories without a pkpass style of their own keep their fields as a generic pass.
+            PassCategory::Generic => PkPassSubtype::Generic(PkPassGeneric {
+                fields,
+            }),
+        };
+
+        PkPass {
+            json: None,
+            l10n,
+            invalid_strings: BTreeMap::new(),
+            locale,
+            manifest_issues: vec![],
+            manifest: None,
+            signature: None,
+            base,
+            barcodes,
+            images: pass.images,
+            subtype,
+        }
     }
 }
 
-impl From<Pass> for PkPass {
-    fn from(pass: Pass) -> Self {
-        unimplemented!("pass to pkpass");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::fixtures::{local, pass};
+
+    fn flight(origin: &str, destination: &str, departure: &str, arrival: &str) -> (FlightDetails, ConversionReport) {
+        let details = FlightDetails {
+            origin: Some(origin.to_owned()),
+            destination: Some(destination.to_owned()),
+            departure: Some(local(departure)),
+            arrival: Some(local(arrival)),
+            ..FlightDetails::default()
+        };
+        let (pass, report) = Pass::from_pkpass(pass(PassCategory::Flight(details)).into());
+        let PassCategory::Flight(details) = pass.category else { panic!("not a flight") };
+        (details, report)
+    }
+
+    fn translated_pass() -> Pass {
+        let mut pass = pass(PassCategory::Generic);
+        pass.fields.primary.push(PassField {
+            key: "greeting".to_owned(),
+            label: None,
+            value: "Hello".to_owned(),
+        });
+        pass.localizations = PassLocalizations {
+            language: Some("en-GB".to_owned()),
+            translations: [("Hello".to_owned(), [("pt-BR".to_owned(), "Olá".to_owned())].into())].into(),
+        };
+        pass.images.push(PassImage {
+            kind: PassImageKind::Logo,
+            scale: 2,
+            locale: Some("pt-BR".to_owned()),
+            data: vec![],
+        });
+        pass
+    }
+
+    #[test]
+    fn lproj_names_use_underscores() {
+        let pkpass = PkPass::from(translated_pass());
+        assert_eq!(pkpass.l10n.keys().collect::<Vec<_>>(), ["en_GB", "pt_BR"]);
+        assert_eq!(pkpass.locale.as_deref(), Some("en_GB"));
+        assert_eq!(pkpass.images[0].pkpass_path(), "pt_BR.lproj/logo@2x.png");
+
+        let (pass, _) = Pass::from_pkpass(pkpass);
+        assert_eq!(pass.localizations.language.as_deref(), Some("en-GB"));
+        assert_eq!(pass.localizations.translations["Hello"].keys().collect::<Vec<_>>(), ["pt-BR"]);
+        assert_eq!(pass.images[0].locale.as_deref(), Some("pt-BR"));
+    }
+
+    #[test]
+    fn languages_without_translations() {
+        let mut pass = pass(PassCategory::Generic);
+        pass.localizations.language = Some("en-GB".to_owned());
+        let mut pkpass = PkPass::from(pass);
+        assert!(pkpass.l10n.is_empty());
+        assert_eq!(pkpass.locale, None);
+
+        // A locale set by hand may have no table either.
+        pkpass.locale = Some("fr".to_owned());
+        pkpass.localize(Some("fr"));
+        let (pass, _) = Pass::from_pkpass(pkpass);
+        assert_eq!(pass.localizations.language, None);
+        assert!(pass.localizations.translations.is_empty());
+    }
+
+    #[test]
+    fn google_languages_are_language_tags() {
+        let gpass = translated_pass().to_gpass(None).unwrap().0;
+        let json = serde_json::to_value(&gpass).unwrap();
+        let header = &json["genericObjects"][0]["header"];
+        assert_eq!(header["defaultValue"]["language"], "en-GB");
+        assert_eq!(header["translatedValues"][0]["language"], "pt-BR");
+    }
+
+    /// A pkpass archive holding `pass` and the given `.lproj` files.
+    fn archive(pass: Pass, files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+        zip.start_file("pass.json", zip::write::FileOptions::default()).unwrap();
+        zip.write_all(PkPass::from(pass).to_json().to_string().as_bytes()).unwrap();
+        for (name, data) in files {
+            zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn invalid_strings_are_skipped() {
+        let data = archive(translated_pass(), &[
+            ("en.lproj/pass.strings", b"\"Hello\" = \"Hi\";\n"),
+            ("fr.lproj/pass.strings", b"\"Hello\" = \"Bonjour\""),
+        ]);
+        let pkpass = PkPass::read(std::io::Cursor::new(data), None, ManifestCheck::Lenient).unwrap();
+        assert_eq!(pkpass.l10n.keys().collect::<Vec<_>>(), ["en"]);
+        assert_eq!(pkpass.invalid_strings.keys().collect::<Vec<_>>(), ["fr.lproj/pass.strings"]);
+
+        let (_, report) = Pass::from_pkpass(pkpass);
+        assert!(matches!(&report.warnings[..], [ConversionWarning::InvalidStrings { file, .. }] if file == "fr.lproj/pass.strings"), "{report:?}");
+    }
+
+    #[test]
+    fn local_date_time_parses_with_and_without_offset() {
+        assert_eq!(local("2026-08-14T08:34:00-04:00").to_iso8601(), "2026-08-14T08:34:00-04:00");
+        assert_eq!(local("2026-08-14T08:34Z").to_iso8601(), "2026-08-14T08:34:00+00:00");
+        assert_eq!(local("2026-08-14T08:34").to_iso8601(), "2026-08-14T08:34:00");
+        assert_eq!(local("2026-08-14T08:34:00.250").offset, None);
+        assert_eq!(LocalDateTime::parse("2026-08-14"), None);
+    }
+
+    #[test]
+    fn flight_times_take_the_airport_zone() {
+        let (details, report) = flight("YUL", "FRA", "2026-08-14T20:30", "2026-08-15T09:40");
+        assert!(report.warnings.is_empty(), "{report:?}");
+        assert_eq!(details.departure.map(|d| d.to_iso8601()).as_deref(), Some("2026-08-14T20:30:00-04:00"));
+        // Only the departure is dated, an earlier arrival time lands the next day.
+        assert_eq!(details.arrival.map(|d| d.to_iso8601()).as_deref(), Some("2026-08-15T09:40:00+02:00"));
+    }
+
+    /// A boarding pass using everything both formats have a place for.
+    fn boarding_pass() -> Pass {
+        let mut pass = pass(PassCategory::Flight(FlightDetails {
+            carrier: Some("AC".to_owned()),
+            flight_number: Some("834".to_owned()),
+            origin: Some("YUL".to_owned()),
+            destination: Some("FRA".to_owned()),
+            origin_gate: Some("52".to_owned()),
+            boarding: Some(local("2026-08-14T19:50:00-04:00")),
+            departure: Some(local("2026-08-14T20:30:00-04:00")),
+            arrival: Some(local("2026-08-15T09:40:00+02:00")),
+            passenger_name: Some("Luc Desmarais".to_owned()),
+            seat: Some("1A".to_owned()),
+            // Anything the barcode has and the pass lacks would be filled in from it.
+            seat_class: Some("F".to_owned()),
+            sequence_number: Some("25".to_owned()),
+            confirmation_code: Some("ABC123".to_owned()),
+            ..FlightDetails::default()
+        }));
+        pass.colors = PassColors {
+            background: Some(PassColor { r: 200, g: 16, b: 46 }),
+            foreground: Some(PassColor { r: 255, g: 255, b: 255 }),
+            label: None,
+        };
+        pass.dates.relevant = Some(local("2026-08-14T19:50:00-04:00").to_fixed().unwrap());
+        pass.barcodes = [BarcodeFormat::Pdf417, BarcodeFormat::Aztec, BarcodeFormat::Qr, BarcodeFormat::Code128]
+            .into_iter()
+            .map(|format| PassBarcode {
+                format,
+                message: "M1DESMARAIS/LUC       EABC123 YULFRAAC 0834 226F001A0025 100".to_owned(),
+                encoding: "iso-8859-1".to_owned(),
+                alt_text: Some("ABC123".to_owned()),
+            })
+            .collect();
+        pass.fields.back.push(PassField {
+            key: "terms".to_owned(),
+            label: Some("Terms".to_owned()),
+            value: "Non-refundable".to_owned(),
+        });
+        pass.images.push(PassImage {
+            kind: PassImageKind::Icon,
+            scale: 2,
+            locale: None,
+            data: b"icon".to_vec(),
+        });
+        pass
+    }
+
+    #[test]
+    fn pkpass_round_trip() {
+        let pass = boarding_pass();
+        let expected = PkPass::from(pass.clone()).to_json();
+
+        let mut data = vec![];
+        pass.write(&mut data, PassFormat::PkPass, None, None).unwrap();
+        let pkpass = PkPass::read(std::io::Cursor::new(data), None, ManifestCheck::Strict).unwrap();
+        let (pass, report) = Pass::from_pkpass(pkpass);
+        assert!(report.is_lossless(), "{report:?}");
+
+        assert_eq!(pass.barcodes.iter().map(|b| b.format).collect::<Vec<_>>(), [BarcodeFormat::Pdf417, BarcodeFormat::Aztec, BarcodeFormat::Qr, BarcodeFormat::Code128]);
+        assert_eq!(pass.images.iter().map(|i| (i.pkpass_path(), i.data.as_slice())).collect::<Vec<_>>(), [("icon@2x.png".to_owned(), &b"icon"[..])]);
+        assert_eq!(PkPass::from(pass).to_json(), expected);
+    }
+
+    #[test]
+    fn unknown_airports_keep_local_times() {
+        let (details, report) = flight("YUL", "QQQ", "2026-08-14T20:30", "2026-08-15T09:40");
+        assert_eq!(report.warnings, [ConversionWarning::UnknownAirport("QQQ".to_owned())]);
+        assert_eq!(details.departure.map(|d| d.to_iso8601()).as_deref(), Some("2026-08-14T20:30:00-04:00"));
+        assert_eq!(details.arrival, Some(local("2026-08-15T09:40")));
     }
 }
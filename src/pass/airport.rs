@@ -0,0 +1,114 @@
+//! Airports by IATA code, from an embedded table of airports with scheduled passenger service,
+//! for the time zones boarding passes leave out. Airports that aren't in it leave times without
+//! an offset.
+
+use std::sync::OnceLock;
+
+use chrono::TimeZone;
+
+/// `code,name,city,country,time zone,latitude,longitude`, sorted by code.
+const AIRPORTS_CSV: &str = include_str!("airports.csv");
+
+#[derive(Debug, Clone)]
+pub struct Airport {
+    /// IATA location code, e.g. `SFO`.
+    pub code: &'static str,
+    pub name: &'static str,
+    pub city: &'static str,
+    /// ISO 3166-1 alpha-2 country code.
+    pub country: &'static str,
+    pub time_zone: chrono_tz::Tz,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Airport {
+    fn parse(line: &'static str) -> Option<Self> {
+        let mut parts = line.split(',');
+        let (Some(code), Some(name), Some(city), Some(country), Some(time_zone), Some(latitude), Some(longitude), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return None;
+        };
+
+        Some(Self {
+            code,
+            name,
+            city,
+            country,
+            time_zone: time_zone.parse().ok()?,
+            latitude: latitude.parse().ok()?,
+            longitude: longitude.parse().ok()?,
+        })
+    }
+
+    /// Places a wall clock time at this airport, with the offset in effect on that date. Times
+    /// repeated when clocks go back are taken as the earlier one, times skipped when they go
+    /// forward as the later.
+    pub fn local_datetime(&self, time: chrono::NaiveDateTime) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        let local = match self.time_zone.from_local_datetime(&time).earliest() {
+            Some(local) => local,
+            None => self.time_zone.from_local_datetime(&(time + chrono::Duration::hours(1))).earliest()?,
+        };
+
+        Some(local.fixed_offset())
+    }
+}
+
+fn airports() -> &'static [Airport] {
+    static AIRPORTS: OnceLock<Vec<Airport>> = OnceLock::new();
+    AIRPORTS.get_or_init(|| {
+        AIRPORTS_CSV.lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| Airport::parse(line).unwrap_or_else(|| panic!("Invalid airport {line:?}")))
+            .collect()
+    })
+}
+
+/// Looks up an airport by IATA code, in any case.
+pub fn lookup(code: &str) -> Option<&'static Airport> {
+    let code = code.trim().to_ascii_uppercase();
+    let airports = airports();
+    let idx = airports.binary_search_by(|airport| airport.code.cmp(code.as_str())).ok()?;
+
+    Some(&airports[idx])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_sorted_and_parses() {
+        let airports = airports();
+        assert_eq!(airports.len(), AIRPORTS_CSV.lines().filter(|line| !line.is_empty()).count());
+        assert!(airports.windows(2).all(|pair| pair[0].code < pair[1].code));
+    }
+
+    #[test]
+    fn lookup_finds_regional_airports() {
+        for (code, zone) in [("BUR", chrono_tz::America::Los_Angeles), ("SNA", chrono_tz::America::Los_Angeles), ("ONT", chrono_tz::America::Los_Angeles)] {
+            assert_eq!(lookup(code).map(|a| a.time_zone), Some(zone), "{code}");
+        }
+        assert_eq!(lookup(" yul ").map(|a| a.code), Some("YUL"));
+        // Closed in 2020, its code is only seen on old passes.
+        assert!(lookup("TXL").is_none());
+    }
+
+    #[test]
+    fn local_datetime_follows_daylight_saving() {
+        let yul = lookup("YUL").unwrap();
+        let at = |s: &str| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(yul.local_datetime(at("2026-08-14 08:34")).unwrap().to_rfc3339(), "2026-08-14T08:34:00-04:00");
+        assert_eq!(yul.local_datetime(at("2026-01-14 08:34")).unwrap().to_rfc3339(), "2026-01-14T08:34:00-05:00");
+        // 02:30 doesn't happen on the night clocks go forward, it's taken as 03:30.
+        assert_eq!(yul.local_datetime(at("2026-03-08 02:30")).unwrap().to_rfc3339(), "2026-03-08T03:30:00-04:00");
+    }
+}
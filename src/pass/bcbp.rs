@@ -0,0 +1,486 @@
+//! IATA Bar Coded Boarding Pass (Resolution 792) messages, as airlines put in pass barcodes.
+//!
+//! A message is a fixed size header, then per leg a fixed size block followed by a variable
+//! size block of conditional items, whose sizes are given in hex. Conditional items may stop
+//! short at any point, so all of them are optional.
+
+use super::{airport, flight_year::YearHints, BarcodeFormat, PassError, FlightDetails, PassBarcode, PassCategory, PassColors, PassDates, PassField, PassFields, PassIssuer, PassLocalizations};
+
+/// A decoded BCBP message. Text is trimmed of its padding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bcbp {
+    /// `SURNAME/GIVEN NAMES`, as printed.
+    pub passenger_name: String,
+    pub electronic_ticket: bool,
+    pub version: Option<u32>,
+    pub passenger_description: Option<String>,
+    pub check_in_source: Option<String>,
+    pub issuance_source: Option<String>,
+    /// Last digit of the year and day of the year the boarding pass was issued.
+    pub issue_date: Option<(u32, u32)>,
+    pub document_type: Option<String>,
+    /// Airline that issued the boarding pass.
+    pub issuer: Option<String>,
+    /// Baggage tag licence plates, each a first tag number and how many follow it.
+    pub baggage_tags: Vec<String>,
+    pub legs: Vec<BcbpLeg>,
+    pub security: Option<BcbpSecurity>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BcbpLeg {
+    /// Booking reference.
+    pub pnr: String,
+    pub origin: String,
+    pub destination: String,
+    pub operating_carrier: String,
+    /// Without leading zeros, e.g. `123A`.
+    pub flight_number: String,
+    /// Day of the year of the flight.
+    pub flight_day: Option<u32>,
+    pub compartment: String,
+    /// Without leading zeros, e.g. `12A`.
+    pub seat: String,
+    /// Check-in sequence number, without leading zeros.
+    pub sequence: String,
+    pub passenger_status: String,
+    /// Airline numeric code and document serial number, the 13 digit ticket number.
+    pub ticket_number: Option<String>,
+    pub selectee: Option<String>,
+    pub international_documentation: Option<String>,
+    pub marketing_carrier: Option<String>,
+    pub frequent_flyer_airline: Option<String>,
+    pub frequent_flyer_number: Option<String>,
+    pub id_ad: Option<String>,
+    pub free_baggage_allowance: Option<String>,
+    pub fast_track: Option<String>,
+    /// Whatever the airline put after the conditional items.
+    pub airline_data: Option<String>,
+}
+
+/// The airline's signature over the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BcbpSecurity {
+    pub kind: String,
+    pub data: String,
+}
+
+/// Reads fixed size items off the front of `data`.
+struct Items<'a> {
+    data: &'a str,
+}
+
+impl<'a> Items<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a str, PassError> {
+        let Some(item) = self.data.get(..len) else {
+            return Err(PassError::InvalidBcbp(format!("ends early, expected {len} more characters")));
+        };
+        self.data = &self.data[len..];
+        Ok(item)
+    }
+
+    /// Takes an item that may be cut off or left blank.
+    fn optional(&mut self, len: usize) -> Option<String> {
+        let len = len.min(self.data.len());
+        let item = self.data[..len].trim();
+        self.data = &self.data[len..];
+        (!item.is_empty()).then(|| item.to_owned())
+    }
+
+    fn hex_size(&mut self) -> Result<usize, PassError> {
+        let size = self.take(2)?;
+        usize::from_str_radix(size, 16)
+            .map_err(|_| PassError::InvalidBcbp(format!("invalid field size {size:?}")))
+    }
+
+    /// Splits off the next `len` characters, or what's left.
+    fn section(&mut self, len: usize) -> Items<'a> {
+        let len = len.min(self.data.len());
+        let section = &self.data[..len];
+        self.data = &self.data[len..];
+        Items { data: section }
+    }
+}
+
+fn trim_zeros(s: &str) -> String {
+    let s = s.trim();
+    let trimmed = s.trim_start_matches('0');
+    if trimmed.is_empty() && !s.is_empty() {
+        "0".to_owned()
+    } else {
+        trimmed.to_owned()
+    }
+}
+
+impl BcbpLeg {
+    fn parse(items: &mut Items, first: bool, bcbp: &mut Bcbp) -> Result<Self, PassError> {
+        let pnr = items.take(7)?.trim().to_owned();
+        let origin = items.take(3)?.trim().to_owned();
+        let destination = items.take(3)?.trim().to_owned();
+        let operating_carrier = items.take(3)?.trim().to_owned();
+        let flight_number = trim_zeros(items.take(5)?);
+        let flight_day = items.take(3)?.trim().parse().ok();
+        let compartment = items.take(1)?.trim().to_owned();
+        let seat = trim_zeros(items.take(4)?);
+        let sequence = trim_zeros(items.take(5)?);
+        let passenger_status = items.take(1)?.trim().to_owned();
+        let size = items.hex_size()?;
+        let mut conditional = items.section(size);
+
+        // Items shared by all legs come with the first.
+        if first && conditional.data.starts_with('>') {
+            conditional.take(1)?;
+            bcbp.version = conditional.take(1)?.parse().ok();
+            let size = conditional.hex_size()?;
+            let mut unique = conditional.section(size);
+            bcbp.passenger_description = unique.optional(1);
+            bcbp.check_in_source = unique.optional(1);
+            bcbp.issuance_source = unique.optional(1);
+            bcbp.issue_date = unique.optional(4)
+                .filter(|date| date.len() == 4 && date.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|date| Some((date[..1].parse().ok()?, date[1..].parse().ok()?)));
+            bcbp.document_type = unique.optional(1);
+            bcbp.issuer = unique.optional(3);
+            bcbp.baggage_tags = (0..3).filter_map(|_| unique.optional(13)).collect();
+        }
+
+        let mut leg = Self {
+            pnr,
+            origin,
+            destination,
+            operating_carrier,
+            flight_number,
+            flight_day,
+            compartment,
+            seat,
+            sequence,
+            passenger_status,
+            ticket_number: None,
+            selectee: None,
+            international_documentation: None,
+            marketing_carrier: None,
+            frequent_flyer_airline: None,
+            frequent_flyer_number: None,
+            id_ad: None,
+            free_baggage_allowance: None,
+            fast_track: None,
+            airline_data: None,
+        };
+        if conditional.data.len() >= 2 {
+            let size = conditional.hex_size()?;
+            let mut repeated = conditional.section(size);
+            let airline_code = repeated.optional(3);
+            let serial_number = repeated.optional(10);
+            leg.ticket_number = match (airline_code, serial_number) {
+                (Some(code), Some(serial)) => Some(format!("{code}{serial}")),
+                (None, Some(serial)) => Some(serial),
+                _ => None,
+            };
+            leg.selectee = repeated.optional(1);
+            leg.international_documentation = repeated.optional(1);
+            leg.marketing_carrier = repeated.optional(3);
+            leg.frequent_flyer_airline = repeated.optional(3);
+            leg.frequent_flyer_number = repeated.optional(16);
+            leg.id_ad = repeated.optional(1);
+            leg.free_baggage_allowance = repeated.optional(3);
+            leg.fast_track = repeated.optional(1);
+        }
+        leg.airline_data = conditional.optional(conditional.data.len());
+
+        Ok(leg)
+    }
+}
+
+/// Parses a BCBP message. Only format `M`, the one in barcodes, is supported.
+pub fn parse(message: &str) -> Result<Bcbp, PassError> {
+    if !message.is_ascii() {
+        return Err(PassError::InvalidBcbp("not ASCII".to_owned()));
+    }
+    let mut items = Items { data: message };
+    if items.take(1)? != "M" {
+        return Err(PassError::InvalidBcbp("does not start with format code M".to_owned()));
+    }
+    let Some(leg_count) = items.take(1)?.parse().ok().filter(|&count: &usize| count > 0) else {
+        return Err(PassError::InvalidBcbp("invalid leg count".to_owned()));
+    };
+
+    let mut bcbp = Bcbp {
+        passenger_name: items.take(20)?.trim().to_owned(),
+        electronic_ticket: items.take(1)? == "E",
+        version: None,
+        passenger_description: None,
+        check_in_source: None,
+        issuance_source: None,
+        issue_date: None,
+        document_type: None,
+        issuer: None,
+        baggage_tags: vec![],
+        legs: vec![],
+        security: None,
+    };
+    for idx in 0..leg_count {
+        let leg = BcbpLeg::parse(&mut items, idx == 0, &mut bcbp)?;
+        bcbp.legs.push(leg);
+    }
+
+    if items.data.starts_with('^') {
+        items.take(1)?;
+        let kind = items.take(1)?.to_owned();
+        let size = items.hex_size()?;
+        let data = items.section(size).data.to_owned();
+        bcbp.security = Some(BcbpSecurity { kind, data });
+    }
+
+    Ok(bcbp)
+}
+
+impl super::Pass {
+    /// Builds a boarding pass from nothing but the BCBP message in its barcode, which is shown
+    /// again as `format`. The message leaves out times, so the pass only has the flight's date,
+    /// its year worked out as for pkpass boarding passes unless `year` is given, and is relevant
+    /// from the start of that day at the origin.
+    ///
+    /// The message doesn't say who issues the pass either, so its organization name and pass type
+    /// identifier are left empty for the caller to fill in. Writing the pass fails until they are.
+    pub fn from_bcbp(message: &str, format: BarcodeFormat, year: Option<i32>) -> Result<Self, PassError> {
+        Self::from_bcbp_on(message, format, year, chrono::Utc::now().date_naive())
+    }
+
+    fn from_bcbp_on(message: &str, format: BarcodeFormat, year: Option<i32>, today: chrono::NaiveDate) -> Result<Self, PassError> {
+        let bcbp = parse(message)?;
+        let Some(leg) = bcbp.legs.first() else {
+            return Err(PassError::InvalidBcbp("no flights".to_owned()));
+        };
+
+        let year_hints = YearHints {
+            year,
+            relevant_date: None,
+            barcode_issue_date: bcbp.issue_date,
+            expiration_date: None,
+            file_date: None,
+            today,
+        };
+        let date = |leg: &BcbpLeg| Some(year_hints.infer_ordinal(leg.flight_day?)?.0);
+        let airport_name = |code: &str| airport::lookup(code).map(|a| a.name.to_owned());
+        let some = |s: &str| (!s.is_empty()).then(|| s.to_owned());
+
+        let flight = FlightDetails {
+            carrier: some(&leg.operating_carrier),
+            flight_number: some(&leg.flight_number),
+            origin: some(&leg.origin),
+            destination: some(&leg.destination),
+            origin_name: airport_name(&leg.origin),
+            destination_name: airport_name(&leg.destination),
+            date: date(leg),
+            passenger_name: some(&bcbp.passenger_name),
+            seat: some(&leg.seat),
+            seat_class: some(&leg.compartment),
+            sequence_number: some(&leg.sequence),
+            confirmation_code: some(&leg.pnr),
+            ticket_number: leg.ticket_number.clone(),
+            frequent_flyer_number: leg.frequent_flyer_number.clone(),
+            ..FlightDetails::default()
+        };
+
+        // Connections share the barcode, but a pass is for one flight.
+        let back = bcbp.legs.iter().enumerate().skip(1).map(|(idx, leg)| {
+            let mut value = format!("{}{} {} to {}", leg.operating_carrier, leg.flight_number, leg.origin, leg.destination);
+            if let Some(date) = date(leg) {
+                value += &date.format(", %d %b").to_string().to_uppercase();
+            }
+            if !leg.seat.is_empty() {
+                value += &format!(", seat {}", leg.seat);
+            }
+            PassField {
+                key: format!("leg{}", idx + 1),
+                label: Some(format!("FLIGHT {}", idx + 1)),
+                value,
+            }
+        }).collect();
+
+        // Midnight needs the origin's time zone, without it the pass only has the date.
+        let relevant = date(leg).zip(airport::lookup(&leg.origin))
+            .and_then(|(date, airport)| airport.local_datetime(date.and_time(chrono::NaiveTime::MIN)));
+
+        let carrier = bcbp.issuer.clone().unwrap_or_else(|| leg.operating_carrier.clone());
+        Ok(Self {
+            serial_number: format!("{}-{}{}-{}", leg.pnr, leg.origin, leg.destination, leg.sequence),
+            type_identifier: String::new(),
+            issuer: PassIssuer::default(),
+            description: "Boarding pass".to_owned(),
+            logo_text: Some(carrier),
+            voided: false,
+            colors: PassColors::default(),
+            barcodes: vec![PassBarcode {
+                format,
+                message: message.to_owned(),
+                encoding: "iso-8859-1".to_owned(),
+                alt_text: None,
+            }],
+            fields: PassFields {
+                back,
+                ..PassFields::default()
+            },
+            dates: PassDates {
+                relevant,
+                expiration: None,
+            },
+            locations: vec![],
+            images: vec![],
+            localizations: PassLocalizations::default(),
+            category: PassCategory::Flight(flight),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pass::{ConversionWarning, ManifestCheck, Pass, PassFormat, PassImage, PassImageKind, PkPass, PkPassSubtype};
+
+    /// The examples from the IATA BCBP implementation guide.
+    const SINGLE_LEG: &str = "M1DESMARAIS/LUC       EABC123 YULFRAAC 0834 226F001A0025 100";
+    const MULTI_LEG: &str = "M2DESMARAIS/LUC       EABC123 YULFRAAC 0834 226F001A0025 14D>6181WW6225BAC 00141234560032A0141234567890 1AC AC 1234567890123    20KYLX58ZDEF456 FRAGVALH 3664 227C012C0002 12E2A0140987654321 1AC AC 1234567890123    2PCNWQ^164GIWVC5EH7JNT684FVNJ91W2QA4DVN5J8K4F0L0GE";
+
+    /// A 2x logo, twice as wide as it's high.
+    fn logo() -> PassImage {
+        let mut data = vec![];
+        image::DynamicImage::new_rgba8(200, 100).write_to(&mut data, image::ImageOutputFormat::Png).unwrap();
+        PassImage {
+            kind: PassImageKind::Logo,
+            scale: 2,
+            locale: None,
+            data,
+        }
+    }
+
+    fn today() -> chrono::NaiveDate {
+        chrono::NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    #[test]
+    fn mandatory_items() {
+        let bcbp = parse(SINGLE_LEG).unwrap();
+        assert_eq!(bcbp.passenger_name, "DESMARAIS/LUC");
+        assert!(bcbp.electronic_ticket);
+        assert_eq!(bcbp.version, None);
+        assert_eq!(bcbp.security, None);
+
+        let [leg] = &bcbp.legs[..] else {
+            panic!("expected one leg");
+        };
+        assert_eq!((leg.pnr.as_str(), leg.origin.as_str(), leg.destination.as_str()), ("ABC123", "YUL", "FRA"));
+        assert_eq!((leg.operating_carrier.as_str(), leg.flight_number.as_str()), ("AC", "834"));
+        assert_eq!(leg.flight_day, Some(226));
+        assert_eq!((leg.compartment.as_str(), leg.seat.as_str(), leg.sequence.as_str()), ("F", "1A", "25"));
+        assert_eq!(leg.ticket_number, None);
+        assert_eq!(leg.airline_data, None);
+    }
+
+    #[test]
+    fn conditional_items_of_every_leg() {
+        let bcbp = parse(MULTI_LEG).unwrap();
+        assert_eq!(bcbp.version, Some(6));
+        assert_eq!(bcbp.passenger_description.as_deref(), Some("1"));
+        assert_eq!(bcbp.issue_date, Some((6, 225)));
+        assert_eq!(bcbp.document_type.as_deref(), Some("B"));
+        assert_eq!(bcbp.issuer.as_deref(), Some("AC"));
+        assert_eq!(bcbp.baggage_tags, ["0014123456003"]);
+
+        let [first, second] = &bcbp.legs[..] else {
+            panic!("expected two legs");
+        };
+        assert_eq!(first.ticket_number.as_deref(), Some("0141234567890"));
+        assert_eq!(first.frequent_flyer_number.as_deref(), Some("1234567890123"));
+        assert_eq!(first.free_baggage_allowance.as_deref(), Some("20K"));
+        assert_eq!(first.fast_track.as_deref(), Some("Y"));
+        assert_eq!(first.airline_data.as_deref(), Some("LX58Z"));
+
+        assert_eq!((second.pnr.as_str(), second.origin.as_str(), second.destination.as_str()), ("DEF456", "FRA", "GVA"));
+        assert_eq!((second.operating_carrier.as_str(), second.flight_number.as_str()), ("LH", "3664"));
+        assert_eq!(second.flight_day, Some(227));
+        assert_eq!((second.seat.as_str(), second.sequence.as_str()), ("12C", "2"));
+        assert_eq!(second.ticket_number.as_deref(), Some("0140987654321"));
+        assert_eq!(second.free_baggage_allowance.as_deref(), Some("2PC"));
+        assert_eq!(second.airline_data.as_deref(), Some("WQ"));
+
+        let security = bcbp.security.unwrap();
+        assert_eq!(security.kind, "1");
+        assert_eq!(security.data, "GIWVC5EH7JNT684FVNJ91W2QA4DVN5J8K4F0L0GE");
+    }
+
+    #[test]
+    fn conditional_items_may_stop_short() {
+        let mandatory = &SINGLE_LEG[..SINGLE_LEG.len() - 2];
+
+        // The unique items stop inside the date of issue, and there are no repeated ones.
+        let bcbp = parse(&format!("{mandatory}0A>6041WW600")).unwrap();
+        assert_eq!(bcbp.version, Some(6));
+        assert_eq!(bcbp.issuance_source.as_deref(), Some("W"));
+        assert_eq!(bcbp.issue_date, None);
+        assert_eq!(bcbp.legs[0].ticket_number, None);
+
+        // The message itself ends before the size it gave.
+        let bcbp = parse(&format!("{mandatory}4D>6181WW6225BAC 0014")).unwrap();
+        assert_eq!(bcbp.issue_date, Some((6, 225)));
+        assert_eq!(bcbp.issuer.as_deref(), Some("AC"));
+        assert_eq!(bcbp.legs[0].ticket_number, None);
+        assert_eq!(bcbp.security, None);
+    }
+
+    #[test]
+    fn mandatory_items_are_required() {
+        assert!(matches!(parse(""), Err(PassError::InvalidBcbp(_))));
+        assert!(matches!(parse(&SINGLE_LEG.replacen('M', "S", 1)), Err(PassError::InvalidBcbp(_))));
+        assert!(matches!(parse(&SINGLE_LEG.replacen("M1", "M0", 1)), Err(PassError::InvalidBcbp(_))));
+        assert!(matches!(parse(&SINGLE_LEG[..40]), Err(PassError::InvalidBcbp(_))));
+        // The second leg is missing.
+        assert!(matches!(parse(&MULTI_LEG[..120]), Err(PassError::InvalidBcbp(_))));
+    }
+
+    #[test]
+    fn relevant_from_the_start_of_the_flight_day() {
+        let pass = Pass::from_bcbp_on(SINGLE_LEG, BarcodeFormat::Aztec, None, today()).unwrap();
+
+        let relevant = pass.dates.relevant.unwrap();
+        assert_eq!(relevant.to_rfc3339(), "2026-08-14T00:00:00-04:00");
+        let PassCategory::Flight(flight) = &pass.category else {
+            panic!("not a flight");
+        };
+        assert_eq!(flight.date, chrono::NaiveDate::from_ymd_opt(2026, 8, 14));
+    }
+
+    #[test]
+    fn identifiers_come_from_the_caller() {
+        let mut pass = Pass::from_bcbp_on(SINGLE_LEG, BarcodeFormat::Aztec, None, today()).unwrap();
+        assert!(pass.type_identifier.is_empty());
+        assert!(pass.issuer.organization_name.is_empty());
+
+        let err = pass.clone().write(vec![], PassFormat::PkPass, None, None).unwrap_err();
+        assert!(matches!(err, PassError::MissingIcon), "{err}");
+
+        pass.images.push(logo());
+        pass.issuer.team_identifier = Some("ABCDE12345".to_owned());
+        let err = pass.clone().write(vec![], PassFormat::PkPass, None, None).unwrap_err();
+        assert!(matches!(err, PassError::MissingIdentifier("passTypeIdentifier")), "{err}");
+        pass.issuer.google_issuer_id = Some("3388000000022000000".to_owned());
+        let err = pass.clone().write(vec![], PassFormat::GPass, None, None).unwrap_err();
+        assert!(matches!(err, PassError::MissingIdentifier("classId")), "{err}");
+
+        pass.type_identifier = "pass.com.example.boarding".to_owned();
+        pass.issuer.organization_name = "Air Canada".to_owned();
+        let mut pkpass = vec![];
+        let report = pass.write(&mut pkpass, PassFormat::PkPass, None, None).unwrap();
+        assert_eq!(report.warnings, [ConversionWarning::IconFromLogo]);
+
+        // The date survives the trip through pass.json, year and all.
+        let pkpass = PkPass::read(std::io::Cursor::new(pkpass), None, ManifestCheck::Strict).unwrap();
+        assert_eq!(pkpass.base.organization_name, "Air Canada");
+        let PkPassSubtype::Flight(flight) = &pkpass.subtype else {
+            panic!("not a boarding pass");
+        };
+        assert_eq!(flight.year(), 2026);
+        let icon = pkpass.images.iter().find(|i| i.pkpass_path() == "icon@2x.png").unwrap();
+        assert_eq!(image::load_from_memory(&icon.data).unwrap().to_rgba8().dimensions(), (58, 58));
+    }
+}
@@ -0,0 +1,151 @@
+//! Everything reading, converting and writing passes can fail with.
+
+use super::{ManifestIssue, SignatureVerdict};
+use crate::barcode::BarcodeError;
+
+/// Why a pass could not be read, converted or written. Paths point into the source document
+/// the way [`super::DroppedValue`] paths do, e.g. `boardingPass.primaryFields[0].value`, with
+/// `pass.json` itself as the empty path.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PassError {
+    /// A key the format requires is absent.
+    MissingKey {
+        path: String,
+    },
+    /// A value is present but isn't the JSON type the format asks for.
+    WrongType {
+        path: String,
+        expected: &'static str,
+    },
+    /// A value isn't one the format allows, e.g. an unknown transit type.
+    InvalidValue {
+        path: String,
+        value: String,
+    },
+    /// A date that isn't a W3C datetime.
+    InvalidDate {
+        path: String,
+        value: String,
+    },
+    /// `pass.json` has none of the style keys, such as `boardingPass` or `coupon`.
+    UnsupportedSubtype,
+    /// `pass.json` has more than one style key.
+    MultipleSubtypes(Vec<&'static str>),
+    /// Files don't match `manifest.json`, when it's checked strictly.
+    ManifestMismatch(Vec<ManifestIssue>),
+    /// A barcode message that isn't IATA BCBP.
+    InvalidBcbp(String),
+    /// A Google Wallet payload that doesn't hold exactly one pass.
+    InvalidGooglePass(String),
+    /// A pass written without a value its format requires, e.g. the `passTypeIdentifier` of one
+    /// made from a boarding pass barcode.
+    MissingIdentifier(&'static str),
+    /// A pass written for Google Wallet without the id of the issuer it's for.
+    MissingIssuerId,
+    /// A Google Wallet issuer id that isn't a number.
+    InvalidIssuerId(String),
+    /// A URL to host Google Wallet images at that isn't http(s).
+    InvalidImageBaseUrl(String),
+    /// A pkpass without an icon, and no logo to make one from.
+    MissingIcon,
+    /// Certificates or keys that can't sign a pass.
+    Certificate(String),
+    /// A signature that isn't valid, when one is required. The verdict says what is wrong.
+    BadSignature(SignatureVerdict),
+    /// The signing certificate was issued for another pass type or team.
+    IdentityMismatch {
+        /// `passTypeIdentifier` or `teamIdentifier`.
+        key: &'static str,
+        pass: String,
+        certificate: Option<String>,
+    },
+    Barcode(BarcodeError),
+    Zip(zip::result::ZipError),
+    Json(serde_json::Error),
+    Base64(base64::DecodeError),
+    Crypto(openssl::error::ErrorStack),
+    Io(std::io::Error),
+}
+
+/// `path`, naming `pass.json` for the document itself.
+fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "pass.json"
+    } else {
+        path
+    }
+}
+
+impl std::fmt::Display for PassError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingKey { path } => write!(f, "{} is missing", display_path(path)),
+            Self::WrongType { path, expected } => write!(f, "{} is not {expected}", display_path(path)),
+            Self::InvalidValue { path, value } => write!(f, "{} has invalid value {value:?}", display_path(path)),
+            Self::InvalidDate { path, value } => write!(f, "{} has invalid date {value:?}", display_path(path)),
+            Self::UnsupportedSubtype => write!(f, "pass.json has no supported pass style"),
+            Self::MultipleSubtypes(keys) => write!(f, "pass.json has more than one pass style: {}", keys.join(", ")),
+            Self::ManifestMismatch(issues) => {
+                let issues: Vec<_> = issues.iter().map(ManifestIssue::to_string).collect();
+                write!(f, "pass does not match its manifest: {}", issues.join("; "))
+            },
+            Self::InvalidBcbp(message) => write!(f, "invalid BCBP message: {message}"),
+            Self::InvalidGooglePass(message) => write!(f, "invalid Google Wallet pass: {message}"),
+            Self::MissingIdentifier(key) => write!(f, "the pass has no {key}"),
+            Self::MissingIssuerId => write!(f, "Google Wallet passes need an issuer id"),
+            Self::InvalidIssuerId(id) => write!(f, "Google Wallet issuer id {id:?} is not a number"),
+            Self::InvalidImageBaseUrl(url) => write!(f, "image base URL {url:?} is not an http(s) URL"),
+            Self::MissingIcon => write!(f, "the pass has no icon, nor a logo to make one from"),
+            Self::Certificate(message) => write!(f, "{message}"),
+            Self::BadSignature(verdict) => write!(f, "bad signature: {verdict}"),
+            Self::IdentityMismatch { key, pass, certificate: Some(certificate) } => write!(f, "certificate is for {key} {certificate:?}, not {pass:?}"),
+            Self::IdentityMismatch { key, pass, certificate: None } => write!(f, "certificate has no {key}, the pass needs {pass:?}"),
+            Self::Barcode(e) => write!(f, "{e}"),
+            Self::Zip(e) => write!(f, "invalid archive: {e}"),
+            Self::Json(e) => write!(f, "invalid JSON: {e}"),
+            Self::Base64(e) => write!(f, "invalid base64: {e}"),
+            Self::Crypto(e) => write!(f, "cryptography failed: {e}"),
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+// Wrapped errors are part of the message, so aren't repeated as sources.
+impl std::error::Error for PassError {}
+
+impl From<BarcodeError> for PassError {
+    fn from(e: BarcodeError) -> Self {
+        Self::Barcode(e)
+    }
+}
+
+impl From<zip::result::ZipError> for PassError {
+    fn from(e: zip::result::ZipError) -> Self {
+        Self::Zip(e)
+    }
+}
+
+impl From<serde_json::Error> for PassError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<base64::DecodeError> for PassError {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::Base64(e)
+    }
+}
+
+impl From<openssl::error::ErrorStack> for PassError {
+    fn from(e: openssl::error::ErrorStack) -> Self {
+        Self::Crypto(e)
+    }
+}
+
+impl From<std::io::Error> for PassError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
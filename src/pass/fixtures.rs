@@ -0,0 +1,31 @@
+//! Passes the tests of every format start from.
+
+use super::*;
+
+/// A pass of `category` with every identifier both formats need and nothing else.
+pub fn pass(category: PassCategory) -> Pass {
+    Pass {
+        serial_number: "123".to_owned(),
+        type_identifier: "pass.com.example".to_owned(),
+        issuer: PassIssuer {
+            organization_name: "Example".to_owned(),
+            team_identifier: Some("ABCDE12345".to_owned()),
+            google_issuer_id: Some("3388000000022000000".to_owned()),
+        },
+        description: "Pass".to_owned(),
+        logo_text: None,
+        voided: false,
+        colors: PassColors::default(),
+        barcodes: vec![],
+        fields: PassFields::default(),
+        dates: PassDates::default(),
+        locations: vec![],
+        images: vec![],
+        localizations: PassLocalizations::default(),
+        category,
+    }
+}
+
+pub fn local(s: &str) -> LocalDateTime {
+    LocalDateTime::parse(s).unwrap()
+}
@@ -0,0 +1,250 @@
+//! Boarding passes date flights by day and month alone, e.g. `17 MAR`. This works out the year.
+
+use chrono::{Datelike, NaiveDate};
+
+/// Where the year of a flight came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YearSource {
+    /// Given by the caller.
+    Override,
+    /// A full departure date.
+    DepartureDate,
+    /// `relevantDate`, usually the departure itself.
+    RelevantDate,
+    /// The boarding pass issue date in the BCBP barcode, the flight being the first date after.
+    BarcodeIssueDate,
+    /// `expirationDate`, usually shortly after the flight.
+    ExpirationDate,
+    /// When `pass.json` was last modified, the flight being the first date after.
+    FileTimestamp,
+    /// The date closest to today.
+    CurrentDate,
+}
+
+/// How far to trust a [`YearSource`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum YearConfidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl YearSource {
+    pub fn confidence(self) -> YearConfidence {
+        match self {
+            Self::Override | Self::DepartureDate | Self::RelevantDate | Self::BarcodeIssueDate => YearConfidence::High,
+            Self::ExpirationDate | Self::FileTimestamp => YearConfidence::Medium,
+            Self::CurrentDate => YearConfidence::Low,
+        }
+    }
+}
+
+impl std::fmt::Display for YearSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Override => write!(f, "the given year"),
+            Self::DepartureDate => write!(f, "the departure date"),
+            Self::RelevantDate => write!(f, "relevantDate"),
+            Self::BarcodeIssueDate => write!(f, "the barcode's issue date"),
+            Self::ExpirationDate => write!(f, "expirationDate"),
+            Self::FileTimestamp => write!(f, "the file timestamp"),
+            Self::CurrentDate => write!(f, "the current date"),
+        }
+    }
+}
+
+impl std::fmt::Display for YearConfidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Low => write!(f, "low"),
+            Self::Medium => write!(f, "medium"),
+            Self::High => write!(f, "high"),
+        }
+    }
+}
+
+/// What a pass says about when it was made and used, most trusted first.
+#[derive(Debug, Clone)]
+pub struct YearHints {
+    pub year: Option<i32>,
+    pub relevant_date: Option<NaiveDate>,
+    /// Last digit of the year and day of the year, see [`super::bcbp::Bcbp::issue_date`].
+    pub barcode_issue_date: Option<(u32, u32)>,
+    pub expiration_date: Option<NaiveDate>,
+    pub file_date: Option<NaiveDate>,
+    pub today: NaiveDate,
+}
+
+/// The year putting the flight, its date in a year given by `date_in`, closest to `reference`.
+fn nearest(date_in: impl Fn(i32) -> Option<NaiveDate>, reference: NaiveDate) -> Option<i32> {
+    (reference.year() - 1..=reference.year() + 1)
+        .filter_map(|year| Some((year, date_in(year)?)))
+        .min_by_key(|(_, date)| (*date - reference).num_days().abs())
+        .map(|(year, _)| year)
+}
+
+/// The first year putting the flight on or after `reference`. Leap days can be years off.
+fn on_or_after(date_in: impl Fn(i32) -> Option<NaiveDate>, reference: NaiveDate) -> Option<i32> {
+    (reference.year()..=reference.year() + 4)
+        .find(|&year| date_in(year).is_some_and(|date| date >= reference))
+}
+
+impl YearHints {
+    /// The barcode issue date, in the last year up to today ending in its digit.
+    fn barcode_issue_date(&self) -> Option<NaiveDate> {
+        let (digit, ordinal) = self.barcode_issue_date?;
+        let year = self.today.year() - (self.today.year() - digit as i32).rem_euclid(10);
+        let date = NaiveDate::from_yo_opt(year, ordinal)?;
+        if date > self.today {
+            NaiveDate::from_yo_opt(year - 10, ordinal)
+        } else {
+            Some(date)
+        }
+    }
+
+    /// Works out the year of a flight on `date`, a day and month such as `17 MAR`.
+    pub fn infer(&self, date: Option<&str>) -> (i32, YearSource) {
+        if let Some(year) = self.year {
+            return (year, YearSource::Override);
+        }
+        // Any leap year reads the 29th of February.
+        let Some(date) = date.and_then(|date| NaiveDate::parse_from_str(&format!("2000 {date}"), "%Y %d %b").ok()) else {
+            return (self.today.year(), YearSource::CurrentDate);
+        };
+        let (month, day) = (date.month(), date.day());
+
+        self.infer_with(|year| NaiveDate::from_ymd_opt(year, month, day))
+    }
+
+    /// Works out the date of a flight on day `ordinal` of the year, as BCBP messages give it.
+    pub fn infer_ordinal(&self, ordinal: u32) -> Option<(NaiveDate, YearSource)> {
+        let date_in = |year| NaiveDate::from_yo_opt(year, ordinal);
+        let (year, source) = match self.year {
+            Some(year) => (year, YearSource::Override),
+            None => self.infer_with(date_in),
+        };
+
+        Some((date_in(year)?, source))
+    }
+
+    fn infer_with(&self, date_in: impl Fn(i32) -> Option<NaiveDate> + Copy) -> (i32, YearSource) {
+        let candidates = [
+            (self.relevant_date.and_then(|d| nearest(date_in, d)), YearSource::RelevantDate),
+            (self.barcode_issue_date().and_then(|d| on_or_after(date_in, d)), YearSource::BarcodeIssueDate),
+            (self.expiration_date.and_then(|d| nearest(date_in, d)), YearSource::ExpirationDate),
+            (self.file_date.and_then(|d| on_or_after(date_in, d)), YearSource::FileTimestamp),
+            (nearest(date_in, self.today), YearSource::CurrentDate),
+        ];
+        candidates.into_iter()
+            .find_map(|(year, source)| Some((year?, source)))
+            .unwrap_or((self.today.year(), YearSource::CurrentDate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn no_hints() -> YearHints {
+        YearHints {
+            year: None,
+            relevant_date: None,
+            barcode_issue_date: None,
+            expiration_date: None,
+            file_date: None,
+            today: date(2026, 10, 18),
+        }
+    }
+
+    #[test]
+    fn nearest_year_across_new_year() {
+        let hints = YearHints {
+            relevant_date: Some(date(2026, 12, 31)),
+            ..no_hints()
+        };
+        assert_eq!(hints.infer(Some("02 JAN")), (2027, YearSource::RelevantDate));
+        assert_eq!(hints.infer(Some("31 DEC")), (2026, YearSource::RelevantDate));
+
+        let hints = YearHints {
+            expiration_date: Some(date(2027, 1, 1)),
+            ..hints
+        };
+        // The relevant date still comes first.
+        assert_eq!(hints.infer(Some("30 DEC")), (2026, YearSource::RelevantDate));
+        let hints = YearHints {
+            relevant_date: None,
+            ..hints
+        };
+        assert_eq!(hints.infer(Some("30 DEC")), (2026, YearSource::ExpirationDate));
+        assert_eq!(hints.infer(Some("01 JUL")), (2027, YearSource::ExpirationDate));
+    }
+
+    #[test]
+    fn flights_follow_the_barcode_issue_date() {
+        // Issued on the last day of 2026.
+        let hints = YearHints {
+            barcode_issue_date: Some((6, 365)),
+            today: date(2027, 1, 5),
+            ..no_hints()
+        };
+        assert_eq!(hints.infer_ordinal(2), Some((date(2027, 1, 2), YearSource::BarcodeIssueDate)));
+        assert_eq!(hints.infer_ordinal(365), Some((date(2026, 12, 31), YearSource::BarcodeIssueDate)));
+
+        // Issue dates can't be after today, so this one is from 2016.
+        let hints = YearHints {
+            barcode_issue_date: Some((6, 300)),
+            ..no_hints()
+        };
+        assert_eq!(hints.barcode_issue_date(), Some(date(2016, 10, 26)));
+        let hints = YearHints {
+            barcode_issue_date: Some((7, 1)),
+            ..no_hints()
+        };
+        assert_eq!(hints.barcode_issue_date(), Some(date(2017, 1, 1)));
+    }
+
+    #[test]
+    fn flights_follow_the_file_date() {
+        let hints = YearHints {
+            file_date: Some(date(2026, 12, 30)),
+            ..no_hints()
+        };
+        assert_eq!(hints.infer(Some("30 DEC")), (2026, YearSource::FileTimestamp));
+        assert_eq!(hints.infer(Some("29 DEC")), (2027, YearSource::FileTimestamp));
+        // The next leap year.
+        assert_eq!(hints.infer(Some("29 FEB")), (2028, YearSource::FileTimestamp));
+    }
+
+    #[test]
+    fn without_hints_the_nearest_to_today() {
+        let hints = no_hints();
+        assert_eq!(hints.infer(Some("14 AUG")), (2026, YearSource::CurrentDate));
+        assert_eq!(hints.infer(Some("17 MAR")), (2027, YearSource::CurrentDate));
+        // Half a year either side of today.
+        assert_eq!(hints.infer(Some("17 APR")), (2027, YearSource::CurrentDate));
+        assert_eq!(hints.infer(Some("20 APR")), (2026, YearSource::CurrentDate));
+        assert_eq!(hints.infer(None), (2026, YearSource::CurrentDate));
+        assert_eq!(hints.infer(Some("MAR 17")), (2026, YearSource::CurrentDate));
+        assert_eq!(YearSource::CurrentDate.confidence(), YearConfidence::Low);
+    }
+
+    #[test]
+    fn given_years_win() {
+        let hints = YearHints {
+            year: Some(2024),
+            relevant_date: Some(date(2026, 12, 31)),
+            ..no_hints()
+        };
+        assert_eq!(hints.infer(Some("02 JAN")), (2024, YearSource::Override));
+        assert_eq!(hints.infer_ordinal(366), Some((date(2024, 12, 31), YearSource::Override)));
+        let hints = YearHints {
+            year: Some(2026),
+            ..hints
+        };
+        assert_eq!(hints.infer_ordinal(366), None);
+    }
+}
@@ -0,0 +1,1922 @@
+//! Google Wallet REST resources, modeled after the `walletobjects/v1` API. Only the parts a pass
+//! conversion can fill or read are modeled; everything else is left to Google's defaults.
+
+use std::io::Read;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use super::{Pass, PassFormat, PassBarcode, BarcodeFormat, PassCategory, PassField, FlightDetails, TransitDetails, TransitMode, EventDetails, ConversionReport, LocalDateTime, CouponDetails, LoyaltyDetails, GiftCardDetails, PassMoney, PassIssuer, PassLocalizations, PassImage, PassImageKind, PassColor, PassColors, PassFields, PassDates, PassLocation, PassError, ConversionWarning, language_tag};
+
+/// Class and object ids are always `{issuer_id}.{suffix}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct ResourceId {
+    pub issuer_id: String,
+    pub suffix: String,
+}
+
+impl ResourceId {
+    /// Builds an id, replacing anything Google does not accept in the suffix with `_`.
+    pub fn new(issuer_id: &str, suffix: &str) -> Self {
+        let suffix = suffix.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '_' })
+            .collect();
+        Self {
+            issuer_id: issuer_id.to_owned(),
+            suffix,
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.issuer_id, self.suffix)
+    }
+}
+
+impl From<ResourceId> for String {
+    fn from(id: ResourceId) -> Self {
+        id.to_string()
+    }
+}
+
+impl TryFrom<String> for ResourceId {
+    type Error = PassError;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        let Some((issuer_id, suffix)) = id.split_once('.') else {
+            return Err(PassError::InvalidGooglePass(format!("resource id {id:?} is not prefixed with an issuer id")));
+        };
+        Ok(Self {
+            issuer_id: issuer_id.to_owned(),
+            suffix: suffix.to_owned(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslatedString {
+    pub language: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedString {
+    pub default_value: TranslatedString,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub translated_values: Vec<TranslatedString>,
+}
+
+impl LocalizedString {
+    /// Language tag used when the source pass does not say which language it is in.
+    pub const DEFAULT_LANGUAGE: &'static str = "en-US";
+
+    pub fn new(value: &str) -> Self {
+        Self {
+            default_value: TranslatedString {
+                language: Self::DEFAULT_LANGUAGE.to_owned(),
+                value: value.to_owned(),
+            },
+            translated_values: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageUri {
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+    pub source_uri: ImageUri,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_description: Option<LocalizedString>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BarcodeType {
+    Aztec,
+    #[serde(rename = "CODE_39")]
+    Code39,
+    #[serde(rename = "CODE_128")]
+    Code128,
+    Codabar,
+    DataMatrix,
+    #[serde(rename = "EAN_8")]
+    Ean8,
+    #[serde(rename = "EAN_13")]
+    Ean13,
+    #[serde(rename = "ITF_14")]
+    Itf14,
+    #[serde(rename = "PDF_417")]
+    Pdf417,
+    QrCode,
+    #[serde(rename = "UPC_A")]
+    UpcA,
+    TextOnly,
+}
+
+impl BarcodeType {
+    pub fn from_format(format: BarcodeFormat) -> Self {
+        match format {
+            BarcodeFormat::Qr => Self::QrCode,
+            BarcodeFormat::Pdf417 => Self::Pdf417,
+            BarcodeFormat::Aztec => Self::Aztec,
+            BarcodeFormat::Code128 => Self::Code128,
+        }
+    }
+
+    /// The pkpass format to show this barcode as. Types Apple Wallet can't draw fall back to the
+    /// closest one it can: QR for Data Matrix, Code 128 for the other linear codes, whose
+    /// characters it can all hold. Text only barcodes have no symbol at all.
+    pub fn to_format(self) -> Option<BarcodeFormat> {
+        match self {
+            Self::QrCode | Self::DataMatrix => Some(BarcodeFormat::Qr),
+            Self::Pdf417 => Some(BarcodeFormat::Pdf417),
+            Self::Aztec => Some(BarcodeFormat::Aztec),
+            Self::Code128 | Self::Code39 | Self::Codabar | Self::Ean8 | Self::Ean13 | Self::Itf14 | Self::UpcA => Some(BarcodeFormat::Code128),
+            Self::TextOnly => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Barcode {
+    #[serde(rename = "type")]
+    pub kind: BarcodeType,
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternate_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_encoding: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextModuleData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub localized_header: Option<LocalizedString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub localized_body: Option<LocalizedString>,
+}
+
+/// An ISO 8601 date or datetime, optionally with an offset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DateTime {
+    pub date: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeInterval {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatLongPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum State {
+    Active,
+    Completed,
+    Expired,
+    Inactive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewStatus {
+    UnderReview,
+    Approved,
+    Rejected,
+    Draft,
+}
+
+/// Amount of money, in millionths of the currency unit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Money {
+    /// int64 values are strings on the wire.
+    #[serde(with = "int64_string")]
+    pub micros: i64,
+    pub currency_code: String,
+}
+
+mod int64_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Int64 {
+            String(String),
+            Number(i64),
+        }
+
+        match Int64::deserialize(deserializer)? {
+            Int64::String(s) => s.parse().map_err(serde::de::Error::custom),
+            Int64::Number(n) => Ok(n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoyaltyPointsBalance {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub string: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub int: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub double: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub money: Option<Money>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoyaltyPoints {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub balance: LoyaltyPointsBalance,
+}
+
+/// Fields every class resource has.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassCommon {
+    pub id: ResourceId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_status: Option<ReviewStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hex_background_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hero_image: Option<Image>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub text_modules_data: Vec<TextModuleData>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<LatLongPoint>,
+}
+
+/// Fields every object resource has.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectCommon {
+    pub id: ResourceId,
+    pub class_id: ResourceId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<State>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub barcode: Option<Barcode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hex_background_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hero_image: Option<Image>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub text_modules_data: Vec<TextModuleData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_time_interval: Option<TimeInterval>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<LatLongPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlightCarrier {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub carrier_iata_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub airline_name: Option<LocalizedString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub airline_logo: Option<Image>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlightHeader {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub carrier: Option<FlightCarrier>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flight_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AirportInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub airport_iata_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub airport_name_override: Option<LocalizedString>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlightClass {
+    #[serde(flatten)]
+    pub common: ClassCommon,
+    pub flight_header: FlightHeader,
+    pub origin: AirportInfo,
+    pub destination: AirportInfo,
+    /// Local time at the origin airport, without an offset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_scheduled_departure_date_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_boarding_date_time: Option<String>,
+    /// Local time at the destination airport, without an offset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_scheduled_arrival_date_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardingAndSeatingInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boarding_group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seat_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seat_class: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrequentFlyerInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequent_flyer_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservationInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmation_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eticket_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequent_flyer_info: Option<FrequentFlyerInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlightObject {
+    #[serde(flatten)]
+    pub common: ObjectCommon,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passenger_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boarding_and_seating_info: Option<BoardingAndSeatingInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reservation_info: Option<ReservationInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventVenue {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<LocalizedString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<LocalizedString>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDateTime {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doors_open: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventTicketClass {
+    #[serde(flatten)]
+    pub common: ClassCommon,
+    pub event_name: LocalizedString,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo: Option<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub venue: Option<EventVenue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_time: Option<EventDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSeat {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seat: Option<LocalizedString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row: Option<LocalizedString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<LocalizedString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate: Option<LocalizedString>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventTicketObject {
+    #[serde(flatten)]
+    pub common: ObjectCommon,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seat_info: Option<EventSeat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket_holder_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RedemptionChannel {
+    Instore,
+    Online,
+    Both,
+    TemporaryPriceReduction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfferClass {
+    #[serde(flatten)]
+    pub common: ClassCommon,
+    pub title: String,
+    pub redemption_channel: RedemptionChannel,
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fine_print: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_image: Option<Image>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfferObject {
+    #[serde(flatten)]
+    pub common: ObjectCommon,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoyaltyClass {
+    #[serde(flatten)]
+    pub common: ClassCommon,
+    pub program_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_logo: Option<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_name_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rewards_tier: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoyaltyObject {
+    #[serde(flatten)]
+    pub common: ObjectCommon,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loyalty_points: Option<LoyaltyPoints>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GiftCardClass {
+    #[serde(flatten)]
+    pub common: ClassCommon,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merchant_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_logo: Option<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card_number_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GiftCardObject {
+    #[serde(flatten)]
+    pub common: ObjectCommon,
+    pub card_number: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<Money>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_update_time: Option<DateTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransitType {
+    Bus,
+    Rail,
+    Tram,
+    Ferry,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransitClass {
+    #[serde(flatten)]
+    pub common: ClassCommon,
+    pub transit_type: TransitType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transit_operator_name: Option<LocalizedString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo: Option<Image>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TripType {
+    RoundTrip,
+    OneWay,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketSeat {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coach: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seat: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_fare_class: Option<LocalizedString>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketLeg {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_station_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_name: Option<LocalizedString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_station_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_name: Option<LocalizedString>,
+    /// Local time, with or without an offset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub departure_date_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arrival_date_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub carriage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket_seat: Option<TicketSeat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransitObject {
+    #[serde(flatten)]
+    pub common: ObjectCommon,
+    pub trip_type: TripType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passenger_names: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket_leg: Option<TicketLeg>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenericClass {
+    #[serde(flatten)]
+    pub common: ClassCommon,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenericObject {
+    #[serde(flatten)]
+    pub common: ObjectCommon,
+    pub card_title: LocalizedString,
+    pub header: LocalizedString,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subheader: Option<LocalizedString>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo: Option<Image>,
+}
+
+#[derive(Debug, Clone)]
+pub enum GoogleWalletClass {
+    Flight(FlightClass),
+    EventTicket(EventTicketClass),
+    Offer(OfferClass),
+    Loyalty(LoyaltyClass),
+    GiftCard(GiftCardClass),
+    Transit(TransitClass),
+    Generic(GenericClass),
+}
+
+#[derive(Debug, Clone)]
+pub enum GoogleWalletObject {
+    Flight(FlightObject),
+    EventTicket(EventTicketObject),
+    Offer(OfferObject),
+    Loyalty(LoyaltyObject),
+    GiftCard(GiftCardObject),
+    Transit(TransitObject),
+    Generic(GenericObject),
+}
+
+/// A single pass: one class and the one object that instantiates it. Serializes as the `payload`
+/// of a "Save to Google Wallet" JWT.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "GoogleWalletPayload", try_from = "GoogleWalletPayload")]
+pub struct GoogleWalletPass {
+    pub class: GoogleWalletClass,
+    pub object: GoogleWalletObject,
+}
+
+/// Resource lists as they appear in a "Save to Google Wallet" JWT payload.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoogleWalletPayload {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flight_classes: Vec<FlightClass>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flight_objects: Vec<FlightObject>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_ticket_classes: Vec<EventTicketClass>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_ticket_objects: Vec<EventTicketObject>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub offer_classes: Vec<OfferClass>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub offer_objects: Vec<OfferObject>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub loyalty_classes: Vec<LoyaltyClass>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub loyalty_objects: Vec<LoyaltyObject>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gift_card_classes: Vec<GiftCardClass>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gift_card_objects: Vec<GiftCardObject>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transit_classes: Vec<TransitClass>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transit_objects: Vec<TransitObject>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generic_classes: Vec<GenericClass>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generic_objects: Vec<GenericObject>,
+}
+
+impl From<GoogleWalletPass> for GoogleWalletPayload {
+    fn from(pass: GoogleWalletPass) -> Self {
+        let mut payload = Self::default();
+        match pass.class {
+            GoogleWalletClass::Flight(c) => payload.flight_classes.push(c),
+            GoogleWalletClass::EventTicket(c) => payload.event_ticket_classes.push(c),
+            GoogleWalletClass::Offer(c) => payload.offer_classes.push(c),
+            GoogleWalletClass::Loyalty(c) => payload.loyalty_classes.push(c),
+            GoogleWalletClass::GiftCard(c) => payload.gift_card_classes.push(c),
+            GoogleWalletClass::Transit(c) => payload.transit_classes.push(c),
+            GoogleWalletClass::Generic(c) => payload.generic_classes.push(c),
+        }
+        match pass.object {
+            GoogleWalletObject::Flight(o) => payload.flight_objects.push(o),
+            GoogleWalletObject::EventTicket(o) => payload.event_ticket_objects.push(o),
+            GoogleWalletObject::Offer(o) => payload.offer_objects.push(o),
+            GoogleWalletObject::Loyalty(o) => payload.loyalty_objects.push(o),
+            GoogleWalletObject::GiftCard(o) => payload.gift_card_objects.push(o),
+            GoogleWalletObject::Transit(o) => payload.transit_objects.push(o),
+            GoogleWalletObject::Generic(o) => payload.generic_objects.push(o),
+        }
+        payload
+    }
+}
+
+impl TryFrom<GoogleWalletPayload> for GoogleWalletPass {
+    type Error = PassError;
+
+    fn try_from(payload: GoogleWalletPayload) -> Result<Self, Self::Error> {
+        fn single<T>(mut values: Vec<T>) -> Result<Option<T>, PassError> {
+            match values.len() {
+                0 => Ok(None),
+                1 => Ok(values.pop()),
+                _ => Err(PassError::InvalidGooglePass("payload holds more than one pass".to_owned())),
+            }
+        }
+
+        let pairs = [
+            single(payload.flight_classes)?.map(GoogleWalletClass::Flight)
+                .zip(single(payload.flight_objects)?.map(GoogleWalletObject::Flight)),
+            single(payload.event_ticket_classes)?.map(GoogleWalletClass::EventTicket)
+                .zip(single(payload.event_ticket_objects)?.map(GoogleWalletObject::EventTicket)),
+            single(payload.offer_classes)?.map(GoogleWalletClass::Offer)
+                .zip(single(payload.offer_objects)?.map(GoogleWalletObject::Offer)),
+            single(payload.loyalty_classes)?.map(GoogleWalletClass::Loyalty)
+                .zip(single(payload.loyalty_objects)?.map(GoogleWalletObject::Loyalty)),
+            single(payload.gift_card_classes)?.map(GoogleWalletClass::GiftCard)
+                .zip(single(payload.gift_card_objects)?.map(GoogleWalletObject::GiftCard)),
+            single(payload.transit_classes)?.map(GoogleWalletClass::Transit)
+                .zip(single(payload.transit_objects)?.map(GoogleWalletObject::Transit)),
+            single(payload.generic_classes)?.map(GoogleWalletClass::Generic)
+                .zip(single(payload.generic_objects)?.map(GoogleWalletObject::Generic)),
+        ];
+
+        let mut found: Vec<_> = pairs.into_iter().flatten().collect();
+        let (class, object) = match found.len() {
+            0 => return Err(PassError::InvalidGooglePass("payload has no matching class and object".to_owned())),
+            1 => found.remove(0),
+            _ => return Err(PassError::InvalidGooglePass("payload holds more than one pass".to_owned())),
+        };
+
+        let pass = Self {
+            class,
+            object,
+        };
+        if pass.object_common().class_id != pass.class_common().id {
+            return Err(PassError::InvalidGooglePass("object does not reference the class it was sent with".to_owned()));
+        }
+        Ok(pass)
+    }
+}
+
+impl GoogleWalletPass {
+    pub fn class_common(&self) -> &ClassCommon {
+        match &self.class {
+            GoogleWalletClass::Flight(c) => &c.common,
+            GoogleWalletClass::EventTicket(c) => &c.common,
+            GoogleWalletClass::Offer(c) => &c.common,
+            GoogleWalletClass::Loyalty(c) => &c.common,
+            GoogleWalletClass::GiftCard(c) => &c.common,
+            GoogleWalletClass::Transit(c) => &c.common,
+            GoogleWalletClass::Generic(c) => &c.common,
+        }
+    }
+
+    /// The image each kind of pass shows as its logo.
+    pub fn logo(&self) -> Option<&Image> {
+        match (&self.class, &self.object) {
+            (GoogleWalletClass::Flight(c), _) => c.flight_header.carrier.as_ref().and_then(|c| c.airline_logo.as_ref()),
+            (GoogleWalletClass::EventTicket(c), _) => c.logo.as_ref(),
+            (GoogleWalletClass::Offer(c), _) => c.title_image.as_ref(),
+            (GoogleWalletClass::Loyalty(c), _) => c.program_logo.as_ref(),
+            (GoogleWalletClass::GiftCard(c), _) => c.program_logo.as_ref(),
+            (GoogleWalletClass::Transit(c), _) => c.logo.as_ref(),
+            (_, GoogleWalletObject::Generic(o)) => o.logo.as_ref(),
+            (GoogleWalletClass::Generic(_), _) => None,
+        }
+    }
+
+    /// Path of [`Self::logo`] in the payload, e.g. `loyaltyClasses[0].programLogo`.
+    fn logo_path(&self) -> String {
+        let path = match &self.class {
+            GoogleWalletClass::Flight(_) => "flightHeader.carrier.airlineLogo",
+            GoogleWalletClass::Offer(_) => "titleImage",
+            GoogleWalletClass::Loyalty(_) | GoogleWalletClass::GiftCard(_) => "programLogo",
+            _ => "logo",
+        };
+        let key = match &self.class {
+            GoogleWalletClass::Generic(_) => self.object_key(),
+            _ => self.class_key(),
+        };
+        format!("{key}[0].{path}")
+    }
+
+    /// Payload key of the list the class is in, e.g. `flightClasses`.
+    pub fn class_key(&self) -> &'static str {
+        match &self.class {
+            GoogleWalletClass::Flight(_) => "flightClasses",
+            GoogleWalletClass::EventTicket(_) => "eventTicketClasses",
+            GoogleWalletClass::Offer(_) => "offerClasses",
+            GoogleWalletClass::Loyalty(_) => "loyaltyClasses",
+            GoogleWalletClass::GiftCard(_) => "giftCardClasses",
+            GoogleWalletClass::Transit(_) => "transitClasses",
+            GoogleWalletClass::Generic(_) => "genericClasses",
+        }
+    }
+
+    /// Payload key of the list the object is in, e.g. `flightObjects`.
+    pub fn object_key(&self) -> &'static str {
+        match &self.object {
+            GoogleWalletObject::Flight(_) => "flightObjects",
+            GoogleWalletObject::EventTicket(_) => "eventTicketObjects",
+            GoogleWalletObject::Offer(_) => "offerObjects",
+            GoogleWalletObject::Loyalty(_) => "loyaltyObjects",
+            GoogleWalletObject::GiftCard(_) => "giftCardObjects",
+            GoogleWalletObject::Transit(_) => "transitObjects",
+            GoogleWalletObject::Generic(_) => "genericObjects",
+        }
+    }
+
+    pub fn object_common(&self) -> &ObjectCommon {
+        match &self.object {
+            GoogleWalletObject::Flight(o) => &o.common,
+            GoogleWalletObject::EventTicket(o) => &o.common,
+            GoogleWalletObject::Offer(o) => &o.common,
+            GoogleWalletObject::Loyalty(o) => &o.common,
+            GoogleWalletObject::GiftCard(o) => &o.common,
+            GoogleWalletObject::Transit(o) => &o.common,
+            GoogleWalletObject::Generic(o) => &o.common,
+        }
+    }
+}
+
+/// Formats a time as Google's offset-less local ISO 8601 form.
+fn local_date_time(dt: &LocalDateTime) -> String {
+    dt.local.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+fn barcode_from_pass(barcode: &PassBarcode) -> Barcode {
+    // Google only knows of UTF-8, anything else is left to its default.
+    let render_encoding = barcode.encoding.eq_ignore_ascii_case("utf-8").then(|| "UTF_8".to_owned());
+    Barcode {
+        kind: BarcodeType::from_format(barcode.format),
+        value: barcode.message.clone(),
+        alternate_text: barcode.alt_text.clone(),
+        render_encoding,
+    }
+}
+
+/// Media type parameter recording the scale of a pkpass image.
+const IMAGE_SCALE_PARAMETER: &str = "scale=";
+
+/// Google fetches images from a URL, so they're linked at their pkpass path under
+/// `image_base_url`, e.g. `https://example.com/passes/1234/logo@2x.png`. Without one the image is
+/// left out and reported.
+fn image_from_pass(pass: &Pass, kind: PassImageKind, image_base_url: Option<&str>, report: &mut ConversionReport) -> Option<Image> {
+    let image = pass.image(kind)?;
+    let Some(base) = image_base_url else {
+        report.warn(ConversionWarning::UnhostedImage(image.pkpass_path()));
+        return None;
+    };
+    Some(Image {
+        source_uri: ImageUri {
+            uri: format!("{}/{}", base.trim_end_matches('/'), image.pkpass_path()),
+            description: None,
+        },
+        content_description: None,
+    })
+}
+
+/// The logo, or the icon for passes without one.
+fn logo_from_pass(pass: &Pass, image_base_url: Option<&str>, report: &mut ConversionReport) -> Option<Image> {
+    let kind = if pass.image(PassImageKind::Logo).is_some() {
+        PassImageKind::Logo
+    } else {
+        PassImageKind::Icon
+    };
+    image_from_pass(pass, kind, image_base_url, report)
+}
+
+/// Reads an image inlined as a data URI that may remember its scale. Hosted images would need
+/// fetching, so they're dropped into `report` at `path`.
+fn image_to_pass(image: &Image, kind: PassImageKind, path: &str, report: &mut ConversionReport) -> Option<PassImage> {
+    let found = data_uri_image(image, kind);
+    if found.is_none() {
+        report.drop_value(format!("{path}.sourceUri.uri"), image.source_uri.uri.clone().into());
+    }
+    found
+}
+
+fn data_uri_image(image: &Image, kind: PassImageKind) -> Option<PassImage> {
+    let (media_type, data) = image.source_uri.uri.strip_prefix("data:")?.split_once(',')?;
+    let mut parameters = media_type.split(';');
+    if parameters.next() != Some("image/png") || parameters.next_back() != Some("base64") {
+        return None;
+    }
+    let scale = parameters
+        .find_map(|p| p.strip_prefix(IMAGE_SCALE_PARAMETER))
+        .and_then(|scale| scale.parse().ok())
+        .unwrap_or(1);
+
+    Some(PassImage {
+        kind,
+        scale,
+        locale: None,
+        data: base64::engine::general_purpose::STANDARD.decode(data).ok()?,
+    })
+}
+
+/// A localized string for `value`, with every translation the pass has of it.
+fn localized_string(pass: &Pass, value: &str) -> LocalizedString {
+    let language = pass.localizations.language.as_deref().unwrap_or(LocalizedString::DEFAULT_LANGUAGE);
+    LocalizedString {
+        default_value: TranslatedString {
+            language: language_tag(language),
+            value: value.to_owned(),
+        },
+        translated_values: pass.localizations.translations.get(value)
+            .into_iter()
+            .flatten()
+            .map(|(language, value)| TranslatedString {
+                language: language_tag(language),
+                value: value.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn text_module_from_field(pass: &Pass, field: &PassField) -> TextModuleData {
+    // The plain header and body are all that's shown unless there's a translation.
+    let translated = |value: &str| {
+        pass.localizations.translations.contains_key(value).then(|| localized_string(pass, value))
+    };
+    TextModuleData {
+        id: Some(field.key.clone()),
+        header: field.label.clone(),
+        body: Some(field.value.clone()),
+        localized_header: field.label.as_deref().and_then(translated),
+        localized_body: translated(&field.value),
+    }
+}
+
+fn flight_from_pass(class_common: ClassCommon, object_common: ObjectCommon, pass: &Pass, flight: &FlightDetails, logo: Option<Image>, report: &mut ConversionReport) -> GoogleWalletPass {
+    // Google takes only a date and time, a date alone is no departure.
+    if let (None, Some(date)) = (&flight.departure, flight.date) {
+        report.warn(ConversionWarning::UnknownDepartureTime(date));
+    }
+    let class = FlightClass {
+        common: class_common,
+        flight_header: FlightHeader {
+            carrier: Some(FlightCarrier {
+                carrier_iata_code: flight.carrier.clone(),
+                airline_name: Some(localized_string(pass, &pass.issuer.organization_name)),
+                airline_logo: logo,
+            }),
+            flight_number: flight.flight_number.clone(),
+        },
+        origin: AirportInfo {
+            airport_iata_code: flight.origin.clone(),
+            terminal: flight.origin_terminal.clone(),
+            gate: flight.origin_gate.clone(),
+            airport_name_override: flight.origin_name.as_deref().map(|name| localized_string(pass, name)),
+        },
+        destination: AirportInfo {
+            airport_iata_code: flight.destination.clone(),
+            terminal: None,
+            gate: None,
+            airport_name_override: flight.destination_name.as_deref().map(|name| localized_string(pass, name)),
+        },
+        local_scheduled_departure_date_time: flight.departure.as_ref().map(local_date_time),
+        local_boarding_date_time: flight.boarding.as_ref().map(local_date_time),
+        local_scheduled_arrival_date_time: flight.arrival.as_ref().map(local_date_time),
+    };
+    let object = FlightObject {
+        common: object_common,
+        passenger_name: flight.passenger_name.clone(),
+        boarding_and_seating_info: Some(BoardingAndSeatingInfo {
+            boarding_group: flight.boarding_group.clone(),
+            seat_number: flight.seat.clone(),
+            seat_class: flight.seat_class.clone(),
+            sequence_number: flight.sequence_number.clone(),
+        }),
+        reservation_info: Some(ReservationInfo {
+            confirmation_code: flight.confirmation_code.clone(),
+            eticket_number: flight.ticket_number.clone(),
+            frequent_flyer_info: flight.frequent_flyer_number.clone().map(|frequent_flyer_number| FrequentFlyerInfo {
+                frequent_flyer_number: Some(frequent_flyer_number),
+            }),
+        }),
+    };
+
+    GoogleWalletPass {
+        class: GoogleWalletClass::Flight(class),
+        object: GoogleWalletObject::Flight(object),
+    }
+}
+
+fn transit_from_pass(class_common: ClassCommon, mut object_common: ObjectCommon, pass: &Pass, transit: &TransitDetails, logo: Option<Image>) -> GoogleWalletPass {
+    let transit_type = match transit.mode {
+        TransitMode::Train => TransitType::Rail,
+        TransitMode::Bus => TransitType::Bus,
+        TransitMode::Boat => TransitType::Ferry,
+        TransitMode::Generic => TransitType::Other,
+    };
+    let operator_name = transit.operator_name.as_deref().unwrap_or(&pass.issuer.organization_name);
+    let class = TransitClass {
+        common: class_common,
+        transit_type,
+        transit_operator_name: Some(localized_string(pass, operator_name)),
+        logo,
+    };
+
+    // Tickets have no slot for the route, so it rides along as a text module.
+    if let Some(route) = &transit.route {
+        if !object_common.text_modules_data.iter().any(|m| m.id.as_deref() == Some("route")) {
+            object_common.text_modules_data.insert(0, TextModuleData {
+                id: Some("route".to_owned()),
+                header: Some("Route".to_owned()),
+                body: Some(route.clone()),
+                localized_header: None,
+                localized_body: None,
+            });
+        }
+    }
+    let has_seat = transit.coach.is_some() || transit.seat.is_some();
+    let ticket_leg = TicketLeg {
+        origin_station_code: transit.origin.clone(),
+        origin_name: transit.origin_name.as_deref().map(|s| localized_string(pass, s)),
+        destination_station_code: transit.destination.clone(),
+        destination_name: transit.destination_name.as_deref().map(|s| localized_string(pass, s)),
+        departure_date_time: transit.departure.map(|d| d.to_iso8601()),
+        arrival_date_time: transit.arrival.map(|d| d.to_iso8601()),
+        platform: transit.platform.clone(),
+        zone: None,
+        carriage: transit.carriage.clone(),
+        ticket_seat: has_seat.then(|| TicketSeat {
+            coach: transit.coach.clone(),
+            seat: transit.seat.clone(),
+            custom_fare_class: None,
+        }),
+    };
+    let object = TransitObject {
+        common: object_common,
+        trip_type: TripType::OneWay,
+        passenger_names: transit.passenger_name.clone(),
+        ticket_number: transit.ticket_number.clone(),
+        ticket_leg: Some(ticket_leg),
+    };
+
+    GoogleWalletPass {
+        class: GoogleWalletClass::Transit(class),
+        object: GoogleWalletObject::Transit(object),
+    }
+}
+
+fn event_from_pass(class_common: ClassCommon, object_common: ObjectCommon, pass: &Pass, event: &EventDetails, logo: Option<Image>) -> GoogleWalletPass {
+    let venue = (event.venue_name.is_some() || event.venue_address.is_some()).then(|| EventVenue {
+        name: event.venue_name.as_deref().map(|s| localized_string(pass, s)),
+        address: event.venue_address.as_deref().map(|s| localized_string(pass, s)),
+    });
+    let date_time = (event.start.is_some() || event.end.is_some()).then(|| EventDateTime {
+        doors_open: None,
+        start: event.start.map(|d| d.to_iso8601()),
+        end: event.end.map(|d| d.to_iso8601()),
+    });
+    let class = EventTicketClass {
+        common: class_common,
+        event_name: localized_string(pass, event.event_name.as_deref().unwrap_or(&pass.description)),
+        event_id: None,
+        logo,
+        venue,
+        date_time,
+    };
+
+    let has_seat = event.seat.is_some() || event.row.is_some() || event.section.is_some() || event.gate.is_some();
+    let object = EventTicketObject {
+        common: object_common,
+        seat_info: has_seat.then(|| EventSeat {
+            seat: event.seat.as_deref().map(|s| localized_string(pass, s)),
+            row: event.row.as_deref().map(|s| localized_string(pass, s)),
+            section: event.section.as_deref().map(|s| localized_string(pass, s)),
+            gate: event.gate.as_deref().map(|s| localized_string(pass, s)),
+        }),
+        ticket_holder_name: event.ticket_holder.clone(),
+        ticket_number: event.ticket_number.clone(),
+    };
+
+    GoogleWalletPass {
+        class: GoogleWalletClass::EventTicket(class),
+        object: GoogleWalletObject::EventTicket(object),
+    }
+}
+
+fn offer_from_pass(class_common: ClassCommon, object_common: ObjectCommon, pass: &Pass, coupon: &CouponDetails, logo: Option<Image>) -> GoogleWalletPass {
+    // A coupon with a barcode is scanned at the till, one without is a code to type in online.
+    let redemption_channel = if pass.barcodes.is_empty() {
+        RedemptionChannel::Online
+    } else {
+        RedemptionChannel::Instore
+    };
+    let class = OfferClass {
+        common: class_common,
+        title: coupon.title.clone().unwrap_or_else(|| pass.description.clone()),
+        redemption_channel,
+        provider: coupon.provider.clone().unwrap_or_else(|| pass.issuer.organization_name.clone()),
+        details: coupon.details.clone(),
+        fine_print: coupon.fine_print.clone(),
+        title_image: logo,
+    };
+
+    GoogleWalletPass {
+        class: GoogleWalletClass::Offer(class),
+        object: GoogleWalletObject::Offer(OfferObject {
+            common: object_common,
+        }),
+    }
+}
+
+/// Converts a decimal amount like `25.50` to micros without going through floats.
+fn money_from_pass(money: &PassMoney) -> Option<Money> {
+    let amount = money.amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > 6 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let negative = whole.starts_with('-');
+    let whole: i64 = whole.parse().ok()?;
+    let fraction: i64 = format!("{fraction:0<6}").parse().ok()?;
+    let fraction = if negative { -fraction } else { fraction };
+    Some(Money {
+        micros: whole.checked_mul(1_000_000)?.checked_add(fraction)?,
+        currency_code: money.currency_code.clone(),
+    })
+}
+
+fn loyalty_from_pass(class_common: ClassCommon, object_common: ObjectCommon, pass: &Pass, loyalty: &LoyaltyDetails, logo: Option<Image>) -> GoogleWalletPass {
+    let class = LoyaltyClass {
+        common: class_common,
+        program_name: loyalty.program_name.clone().unwrap_or_else(|| pass.issuer.organization_name.clone()),
+        program_logo: logo,
+        account_id_label: None,
+        account_name_label: None,
+        rewards_tier: None,
+    };
+    let loyalty_points = loyalty.points_balance.as_ref().map(|balance| LoyaltyPoints {
+        label: loyalty.points_label.clone(),
+        balance: match balance.parse::<i64>() {
+            Ok(int) => LoyaltyPointsBalance {
+                string: None,
+                int: Some(int),
+                double: None,
+                money: None,
+            },
+            Err(_) => LoyaltyPointsBalance {
+                string: Some(balance.clone()),
+                int: None,
+                double: None,
+                money: None,
+            },
+        },
+    });
+    let object = LoyaltyObject {
+        common: object_common,
+        account_id: loyalty.account_id.clone(),
+        account_name: loyalty.account_name.clone(),
+        loyalty_points,
+    };
+
+    GoogleWalletPass {
+        class: GoogleWalletClass::Loyalty(class),
+        object: GoogleWalletObject::Loyalty(object),
+    }
+}
+
+fn gift_card_from_pass(class_common: ClassCommon, object_common: ObjectCommon, pass: &Pass, gift_card: &GiftCardDetails, logo: Option<Image>, report: &mut ConversionReport) -> GoogleWalletPass {
+    let balance = gift_card.balance.as_ref().and_then(|balance| {
+        let money = money_from_pass(balance);
+        if money.is_none() {
+            report.warn(ConversionWarning::InvalidBalance(balance.amount.clone()));
+        }
+        money
+    });
+    let class = GiftCardClass {
+        common: class_common,
+        merchant_name: Some(gift_card.merchant_name.clone().unwrap_or_else(|| pass.issuer.organization_name.clone())),
+        program_logo: logo,
+        card_number_label: None,
+        pin_label: None,
+    };
+    let object = GiftCardObject {
+        common: object_common,
+        // Google requires a card number, the serial number is the closest stand in.
+        card_number: gift_card.card_number.clone().unwrap_or_else(|| pass.serial_number.clone()),
+        pin: gift_card.pin.clone(),
+        balance,
+        balance_update_time: None,
+    };
+
+    GoogleWalletPass {
+        class: GoogleWalletClass::GiftCard(class),
+        object: GoogleWalletObject::GiftCard(object),
+    }
+}
+
+/// The pkpass header row, logo text and header fields, becomes the card title and subheader; the
+/// first primary field becomes the header. Every other field turns into a text module, in the
+/// order it appears on the pass.
+fn generic_from_pass(class_common: ClassCommon, mut object_common: ObjectCommon, pass: &Pass, logo: Option<Image>) -> GoogleWalletPass {
+    let card_title = pass.logo_text.as_deref().unwrap_or(&pass.issuer.organization_name);
+    let (subheader, header_rest) = match pass.fields.header.split_first() {
+        Some((subheader, rest)) => (Some(localized_string(pass, &subheader.value)), rest),
+        None => (None, &[][..]),
+    };
+    let (header, primary_rest) = match pass.fields.primary.split_first() {
+        Some((header, rest)) => (localized_string(pass, &header.value), rest),
+        None => (localized_string(pass, &pass.description), &[][..]),
+    };
+    object_common.text_modules_data = header_rest.iter()
+        .chain(primary_rest)
+        .chain(&pass.fields.secondary)
+        .chain(&pass.fields.auxiliary)
+        .chain(&pass.fields.back)
+        .map(|f| text_module_from_field(pass, f))
+        .collect();
+
+    let object = GenericObject {
+        common: object_common,
+        card_title: localized_string(pass, card_title),
+        header,
+        subheader,
+        logo,
+    };
+
+    GoogleWalletPass {
+        class: GoogleWalletClass::Generic(GenericClass {
+            common: class_common,
+        }),
+        object: GoogleWalletObject::Generic(object),
+    }
+}
+
+/// Whether `id` could be a Google Wallet issuer id, which are all digits.
+fn is_issuer_id(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_http_url(url: &str) -> bool {
+    ["https://", "http://"].iter()
+        .any(|scheme| url.get(..scheme.len()).is_some_and(|s| s.eq_ignore_ascii_case(scheme)) && url.len() > scheme.len())
+}
+
+impl Pass {
+    /// Converts to a Google Wallet pass, reporting what it has no place for. Fails unless the
+    /// pass names the Google Wallet issuer it's for.
+    ///
+    /// Google only shows images it fetches itself, so they're linked under `image_base_url` at
+    /// their path in the pkpass, where the pkpass's files are expected to be hosted. Without it,
+    /// images are left out.
+    pub fn to_gpass(self, image_base_url: Option<&str>) -> Result<(GoogleWalletPass, ConversionReport), PassError> {
+        let pass = self;
+        if let Some(url) = image_base_url.filter(|url| !is_http_url(url)) {
+            return Err(PassError::InvalidImageBaseUrl(url.to_owned()));
+        }
+        let mut report = ConversionReport::default();
+        let issuer_id = match pass.issuer.google_issuer_id.clone() {
+            Some(id) if is_issuer_id(&id) => id,
+            Some(id) => return Err(PassError::InvalidIssuerId(id)),
+            None => return Err(PassError::MissingIssuerId),
+        };
+        if pass.type_identifier.is_empty() {
+            return Err(PassError::MissingIdentifier("classId"));
+        }
+        if pass.issuer.organization_name.is_empty() {
+            return Err(PassError::MissingIdentifier("issuerName"));
+        }
+        let class_id = ResourceId::new(&issuer_id, &pass.type_identifier);
+        let hex_background_color = pass.colors.background.map(|c| c.to_hex_string());
+        let locations: Vec<_> = pass.locations.iter().map(|l| LatLongPoint {
+            latitude: l.latitude,
+            longitude: l.longitude,
+        }).collect();
+
+        let class_common = ClassCommon {
+            id: class_id.clone(),
+            issuer_name: Some(pass.issuer.organization_name.clone()),
+            review_status: Some(ReviewStatus::UnderReview),
+            hex_background_color: hex_background_color.clone(),
+            hero_image: image_from_pass(&pass, PassImageKind::Strip, image_base_url, &mut report),
+            text_modules_data: vec![],
+            locations: locations.clone(),
+        };
+        let state = if pass.voided {
+            State::Inactive
+        } else {
+            State::Active
+        };
+        let valid_time_interval = pass.dates.expiration.map(|end| TimeInterval {
+            start: None,
+            end: Some(DateTime {
+                date: end.to_rfc3339(),
+            }),
+        });
+        let object_common = ObjectCommon {
+            id: ResourceId::new(&issuer_id, &pass.serial_number),
+            class_id,
+            state: Some(state),
+            barcode: pass.primary_barcode(PassFormat::GPass).map(barcode_from_pass),
+            hex_background_color,
+            hero_image: None,
+            text_modules_data: pass.fields.back.iter().map(|f| text_module_from_field(&pass, f)).collect(),
+            valid_time_interval,
+            locations,
+        };
+
+        let logo = logo_from_pass(&pass, image_base_url, &mut report);
+
+        let gpass = match &pass.category {
+            PassCategory::Flight(flight) => flight_from_pass(class_common, object_common, &pass, flight, logo, &mut report),
+            PassCategory::Transit(transit) => transit_from_pass(class_common, object_common, &pass, transit, logo),
+            PassCategory::Event(event) => event_from_pass(class_common, object_common, &pass, event, logo),
+            PassCategory::Coupon(coupon) => offer_from_pass(class_common, object_common, &pass, coupon, logo),
+            PassCategory::Loyalty(loyalty) => loyalty_from_pass(class_common, object_common, &pass, loyalty, logo),
+            PassCategory::GiftCard(gift_card) => gift_card_from_pass(class_common, object_common, &pass, gift_card, logo, &mut report),
+            PassCategory::Generic => generic_from_pass(class_common, object_common, &pass, logo),
+        };
+        Ok((gpass, report))
+    }
+}
+
+impl GoogleWalletPass {
+    /// Prefix of "Save to Google Wallet" links, the JWT follows it.
+    const SAVE_URL_PREFIX: &'static str = "https://pay.google.com/gp/v/save/";
+
+    /// Reads a pass from a JSON payload, the JSON claims of a "Save to Google Wallet" JWT, or the
+    /// JWT (or link) itself. The JWT signature is not checked.
+    pub fn read<R: Read>(mut gpass_r: R) -> Result<Self, PassError> {
+        let mut contents = String::new();
+        gpass_r.read_to_string(&mut contents)?;
+        let contents = contents.trim();
+
+        let json: serde_json::Value = if contents.starts_with('{') {
+            serde_json::from_str(contents)?
+        } else {
+            let jwt = contents.strip_prefix(Self::SAVE_URL_PREFIX).unwrap_or(contents);
+            let Some(claims) = jwt.split('.').nth(1) else {
+                return Err(PassError::InvalidGooglePass("JWT is missing its claims".to_owned()));
+            };
+            let claims = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(claims.trim_end_matches('='))?;
+            serde_json::from_slice(&claims)?
+        };
+
+        // JWT claims wrap the resources in `payload`.
+        let payload = match json {
+            serde_json::Value::Object(mut claims) if claims.contains_key("payload") => claims.remove("payload").unwrap_or_default(),
+            payload => payload,
+        };
+
+        Ok(serde_json::from_value(payload)?)
+    }
+}
+
+/// Parses one of Google's datetimes, recording it in `report` if it doesn't parse.
+fn parse_date_time(report: &mut ConversionReport, path: &str, raw: Option<&str>) -> Option<LocalDateTime> {
+    report.parse_or_drop(path, raw, LocalDateTime::parse)
+}
+
+fn barcode_to_pass(barcode: &Barcode) -> Option<PassBarcode> {
+    let format = barcode.kind.to_format()?;
+    let encoding = match barcode.render_encoding.as_deref() {
+        Some("UTF_8") => "utf-8",
+        _ => "iso-8859-1",
+    };
+    Some(PassBarcode {
+        format,
+        message: barcode.value.clone(),
+        encoding: encoding.to_owned(),
+        alt_text: barcode.alternate_text.clone(),
+    })
+}
+
+/// Gathers the translations of every localized string in `value`, a serialized Google pass.
+fn collect_localizations(value: &serde_json::Value, localizations: &mut PassLocalizations) {
+    match value {
+        serde_json::Value::Object(obj) => {
+            if let Ok(localized) = serde_json::from_value::<LocalizedString>(value.clone()) {
+                let default = localized.default_value;
+                localizations.language.get_or_insert_with(|| language_tag(&default.language));
+                for translated in localized.translated_values {
+                    localizations.translations.entry(default.value.clone())
+                        .or_default()
+                        .entry(language_tag(&translated.language))
+                        .or_insert(translated.value);
+                }
+                return;
+            }
+            obj.values().for_each(|v| collect_localizations(v, localizations));
+        },
+        serde_json::Value::Array(values) => values.iter().for_each(|v| collect_localizations(v, localizations)),
+        _ => {},
+    }
+}
+
+fn text_module_to_field(idx: usize, module: &TextModuleData) -> Option<PassField> {
+    let value = module.body.clone()
+        .or_else(|| module.localized_body.as_ref().map(|b| b.default_value.value.clone()))?;
+    Some(PassField {
+        key: module.id.clone().unwrap_or_else(|| format!("text{idx}")),
+        label: module.header.clone()
+            .or_else(|| module.localized_header.as_ref().map(|h| h.default_value.value.clone())),
+        value,
+    })
+}
+
+fn flight_to_details(class: &FlightClass, object: &FlightObject, report: &mut ConversionReport) -> FlightDetails {
+    let seating = object.boarding_and_seating_info.as_ref();
+    let reservation = object.reservation_info.as_ref();
+    let departure = class.local_scheduled_departure_date_time.as_deref();
+    // A departure without a time is a flight whose times aren't known yet.
+    let date = departure.and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    FlightDetails {
+        carrier: class.flight_header.carrier.as_ref().and_then(|c| c.carrier_iata_code.clone()),
+        flight_number: class.flight_header.flight_number.clone(),
+        origin: class.origin.airport_iata_code.clone(),
+        destination: class.destination.airport_iata_code.clone(),
+        origin_name: class.origin.airport_name_override.as_ref().map(|n| n.default_value.value.clone()),
+        destination_name: class.destination.airport_name_override.as_ref().map(|n| n.default_value.value.clone()),
+        origin_terminal: class.origin.terminal.clone(),
+        origin_gate: class.origin.gate.clone(),
+        date,
+        boarding: parse_date_time(report, "flightClasses[0].localBoardingDateTime", class.local_boarding_date_time.as_deref()),
+        departure: parse_date_time(report, "flightClasses[0].localScheduledDepartureDateTime", departure.filter(|_| date.is_none())),
+        arrival: parse_date_time(report, "flightClasses[0].localScheduledArrivalDateTime", class.local_scheduled_arrival_date_time.as_deref()),
+        passenger_name: object.passenger_name.clone(),
+        seat: seating.and_then(|s| s.seat_number.clone()),
+        seat_class: seating.and_then(|s| s.seat_class.clone()),
+        boarding_group: seating.and_then(|s| s.boarding_group.clone()),
+        sequence_number: seating.and_then(|s| s.sequence_number.clone()),
+        status: None,
+        confirmation_code: reservation.and_then(|r| r.confirmation_code.clone()),
+        ticket_number: reservation.and_then(|r| r.eticket_number.clone()),
+        frequent_flyer_number: reservation.and_then(|r| r.frequent_flyer_info.as_ref()).and_then(|f| f.frequent_flyer_number.clone()),
+    }
+}
+
+fn event_to_details(class: &EventTicketClass, object: &EventTicketObject, report: &mut ConversionReport) -> EventDetails {
+    let venue = class.venue.as_ref();
+    let date_time = class.date_time.as_ref();
+    let seat_info = object.seat_info.as_ref();
+    let localized = |s: Option<&LocalizedString>| s.map(|s| s.default_value.value.clone());
+    EventDetails {
+        event_name: Some(class.event_name.default_value.value.clone()),
+        venue_name: localized(venue.and_then(|v| v.name.as_ref())),
+        venue_address: localized(venue.and_then(|v| v.address.as_ref())),
+        start: parse_date_time(report, "eventTicketClasses[0].dateTime.start", date_time.and_then(|d| d.start.as_deref())),
+        end: parse_date_time(report, "eventTicketClasses[0].dateTime.end", date_time.and_then(|d| d.end.as_deref())),
+        gate: localized(seat_info.and_then(|s| s.gate.as_ref())),
+        section: localized(seat_info.and_then(|s| s.section.as_ref())),
+        row: localized(seat_info.and_then(|s| s.row.as_ref())),
+        seat: localized(seat_info.and_then(|s| s.seat.as_ref())),
+        ticket_holder: object.ticket_holder_name.clone(),
+        ticket_number: object.ticket_number.clone(),
+    }
+}
+
+fn offer_to_details(class: &OfferClass) -> CouponDetails {
+    CouponDetails {
+        title: Some(class.title.clone()),
+        provider: Some(class.provider.clone()),
+        details: class.details.clone(),
+        fine_print: class.fine_print.clone(),
+    }
+}
+
+/// Formats micros as a decimal amount, keeping at least two decimals.
+fn money_to_pass(money: &Money) -> PassMoney {
+    let sign = if money.micros < 0 { "-" } else { "" };
+    let micros = money.micros.unsigned_abs();
+    let fraction = format!("{:06}", micros % 1_000_000);
+    let fraction = fraction.trim_end_matches('0');
+    PassMoney {
+        amount: format!("{sign}{}.{fraction:0<2}", micros / 1_000_000),
+        currency_code: money.currency_code.clone(),
+    }
+}
+
+fn loyalty_to_details(class: &LoyaltyClass, object: &LoyaltyObject) -> LoyaltyDetails {
+    let points = object.loyalty_points.as_ref();
+    let balance = points.map(|p| &p.balance).and_then(|b| {
+        b.string.clone()
+            .or_else(|| b.int.map(|i| i.to_string()))
+            .or_else(|| b.double.map(|d| d.to_string()))
+            .or_else(|| b.money.as_ref().map(|m| money_to_pass(m).amount))
+    });
+    LoyaltyDetails {
+        program_name: Some(class.program_name.clone()),
+        account_id: object.account_id.clone(),
+        account_name: object.account_name.clone(),
+        points_label: points.and_then(|p| p.label.clone()),
+        points_balance: balance,
+    }
+}
+
+fn gift_card_to_details(class: &GiftCardClass, object: &GiftCardObject) -> GiftCardDetails {
+    GiftCardDetails {
+        merchant_name: class.merchant_name.clone(),
+        card_number: Some(object.card_number.clone()),
+        pin: object.pin.clone(),
+        balance: object.balance.as_ref().map(money_to_pass),
+    }
+}
+
+fn transit_to_details(class: &TransitClass, object: &TransitObject, report: &mut ConversionReport) -> TransitDetails {
+    let mode = match class.transit_type {
+        TransitType::Rail | TransitType::Tram => TransitMode::Train,
+        TransitType::Bus => TransitMode::Bus,
+        TransitType::Ferry => TransitMode::Boat,
+        TransitType::Other => TransitMode::Generic,
+    };
+    let leg = object.ticket_leg.as_ref();
+    let seat = leg.and_then(|l| l.ticket_seat.as_ref());
+    let localized = |s: Option<&LocalizedString>| s.map(|s| s.default_value.value.clone());
+    let route = object.common.text_modules_data.iter()
+        .find(|m| m.id.as_deref() == Some("route"))
+        .and_then(|m| m.body.clone());
+    TransitDetails {
+        mode,
+        operator_name: localized(class.transit_operator_name.as_ref()),
+        route,
+        origin: leg.and_then(|l| l.origin_station_code.clone()),
+        origin_name: localized(leg.and_then(|l| l.origin_name.as_ref())),
+        destination: leg.and_then(|l| l.destination_station_code.clone()),
+        destination_name: localized(leg.and_then(|l| l.destination_name.as_ref())),
+        departure: parse_date_time(report, "transitObjects[0].ticketLeg.departureDateTime", leg.and_then(|l| l.departure_date_time.as_deref())),
+        arrival: parse_date_time(report, "transitObjects[0].ticketLeg.arrivalDateTime", leg.and_then(|l| l.arrival_date_time.as_deref())),
+        platform: leg.and_then(|l| l.platform.clone()),
+        carriage: leg.and_then(|l| l.carriage.clone()),
+        coach: seat.and_then(|s| s.coach.clone()),
+        seat: seat.and_then(|s| s.seat.clone()),
+        passenger_name: object.passenger_names.clone(),
+        ticket_number: object.ticket_number.clone(),
+    }
+}
+
+impl Pass {
+    /// Converts a Google Wallet pass, reporting the values the neutral pass has no place for.
+    pub fn from_gpass(gpass: GoogleWalletPass) -> (Self, ConversionReport) {
+        let mut report = ConversionReport::default();
+        let object_path = format!("{}[0]", gpass.object_key());
+        let class_common = gpass.class_common().clone();
+        let object_common = gpass.object_common().clone();
+        let issuer_name = class_common.issuer_name.clone().unwrap_or_default();
+        let hero_image = match &object_common.hero_image {
+            Some(image) => Some((image, format!("{object_path}.heroImage"))),
+            None => class_common.hero_image.as_ref().map(|image| (image, format!("{}[0].heroImage", gpass.class_key()))),
+        };
+        let logo = gpass.logo().map(|image| (image, gpass.logo_path()));
+        let images = [(PassImageKind::Strip, hero_image), (PassImageKind::Logo, logo)]
+            .into_iter()
+            .filter_map(|(kind, image)| image.and_then(|(image, path)| image_to_pass(image, kind, &path, &mut report)))
+            .collect();
+        let mut localizations = PassLocalizations::default();
+        if let Ok(json) = serde_json::to_value(&gpass) {
+            collect_localizations(&json, &mut localizations);
+        }
+
+        let mut fields = PassFields {
+            back: class_common.text_modules_data.iter()
+                .chain(object_common.text_modules_data.iter())
+                .enumerate()
+                .filter_map(|(idx, module)| text_module_to_field(idx, module))
+                .collect(),
+            ..PassFields::default()
+        };
+        // Text only barcodes have no pkpass equivalent, keep what they show.
+        if let Some(barcode) = object_common.barcode.as_ref().filter(|b| b.kind == BarcodeType::TextOnly) {
+            fields.back.push(PassField {
+                key: "barcode".to_owned(),
+                label: barcode.alternate_text.clone(),
+                value: barcode.value.clone(),
+            });
+        }
+        let mut logo_text = class_common.issuer_name.clone();
+
+        let (category, description) = match (gpass.class, gpass.object) {
+            (GoogleWalletClass::Flight(class), GoogleWalletObject::Flight(object)) => {
+                (PassCategory::Flight(flight_to_details(&class, &object, &mut report)), "Boarding pass".to_owned())
+            },
+            (GoogleWalletClass::EventTicket(class), GoogleWalletObject::EventTicket(object)) => {
+                let description = class.event_name.default_value.value.clone();
+                (PassCategory::Event(event_to_details(&class, &object, &mut report)), description)
+            },
+            (GoogleWalletClass::Offer(class), GoogleWalletObject::Offer(_)) => {
+                (PassCategory::Coupon(offer_to_details(&class)), class.title.clone())
+            },
+            (GoogleWalletClass::Loyalty(class), GoogleWalletObject::Loyalty(object)) => {
+                (PassCategory::Loyalty(loyalty_to_details(&class, &object)), class.program_name.clone())
+            },
+            (GoogleWalletClass::GiftCard(class), GoogleWalletObject::GiftCard(object)) => {
+                let description = class.merchant_name.clone().unwrap_or_else(|| "Gift card".to_owned());
+                (PassCategory::GiftCard(gift_card_to_details(&class, &object)), description)
+            },
+            (GoogleWalletClass::Generic(_), GoogleWalletObject::Generic(object)) => {
+                let header = object.header.default_value.value;
+                fields.primary.push(PassField {
+                    key: "header".to_owned(),
+                    label: None,
+                    value: header.clone(),
+                });
+                if let Some(subheader) = object.subheader {
+                    fields.header.push(PassField {
+                        key: "subheader".to_owned(),
+                        label: None,
+                        value: subheader.default_value.value,
+                    });
+                }
+                logo_text = Some(object.card_title.default_value.value);
+                (PassCategory::Generic, header)
+            },
+            (GoogleWalletClass::Transit(class), GoogleWalletObject::Transit(object)) => {
+                // The route text module is read into the details, keep it out of the back fields.
+                fields.back.retain(|f| f.key != "route");
+                (PassCategory::Transit(transit_to_details(&class, &object, &mut report)), "Transit ticket".to_owned())
+            },
+            // Reading pairs classes with objects of the same kind, so only passes put together
+            // by hand end up here.
+            _ => {
+                let title = issuer_name.clone();
+                fields.primary.push(PassField {
+                    key: "title".to_owned(),
+                    label: None,
+                    value: title.clone(),
+                });
+                (PassCategory::Generic, title)
+            },
+        };
+
+        let background = object_common.hex_background_color.as_deref()
+            .or(class_common.hex_background_color.as_deref())
+            .and_then(PassColor::parse);
+        let interval = object_common.valid_time_interval.as_ref();
+        // Google shows times without an offset in the viewer's zone, which a pkpass has no way to say.
+        let mut interval_date = |key: &str, date: Option<&DateTime>| {
+            let path = format!("{object_path}.validTimeInterval.{key}.date");
+            report.parse_or_drop(&path, date.map(|d| d.date.as_str()), |s| LocalDateTime::parse(s)?.to_fixed())
+        };
+        let dates = PassDates {
+            relevant: interval_date("start", interval.and_then(|i| i.start.as_ref())),
+            expiration: interval_date("end", interval.and_then(|i| i.end.as_ref())),
+        };
+        let locations = if object_common.locations.is_empty() {
+            &class_common.locations
+        } else {
+            &object_common.locations
+        };
+
+        let pass = Pass {
+            serial_number: object_common.id.suffix.clone(),
+            type_identifier: class_common.id.suffix.clone(),
+            issuer: PassIssuer {
+                organization_name: issuer_name,
+                team_identifier: None,
+                google_issuer_id: Some(class_common.id.issuer_id.clone()),
+            },
+            description,
+            logo_text,
+            voided: object_common.state == Some(State::Inactive),
+            colors: PassColors {
+                background,
+                foreground: None,
+                label: None,
+            },
+            barcodes: object_common.barcode.as_ref().and_then(barcode_to_pass).into_iter().collect(),
+            fields,
+            dates,
+            locations: locations.iter().map(|l| PassLocation {
+                latitude: l.latitude,
+                longitude: l.longitude,
+                altitude: None,
+                relevant_text: None,
+            }).collect(),
+            images,
+            localizations,
+            category,
+        };
+
+        (pass, report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pass::fixtures::{local, pass};
+
+    #[test]
+    fn issuer_id_is_required() {
+        let mut unnamed = pass(PassCategory::Generic);
+        unnamed.issuer.google_issuer_id = None;
+        assert!(matches!(unnamed.to_gpass(None), Err(PassError::MissingIssuerId)));
+
+        let mut named = pass(PassCategory::Generic);
+        named.issuer.google_issuer_id = Some("ABCDE12345".to_owned());
+        assert!(matches!(named.to_gpass(None), Err(PassError::InvalidIssuerId(id)) if id == "ABCDE12345"));
+
+        let gpass = pass(PassCategory::Generic).to_gpass(None).unwrap().0;
+        assert_eq!(gpass.class_common().id.issuer_id, "3388000000022000000");
+        assert_eq!(gpass.object_common().id.issuer_id, "3388000000022000000");
+    }
+
+    #[test]
+    fn date_only_flight_has_no_time() {
+        let flight = FlightDetails {
+            date: chrono::NaiveDate::from_ymd_opt(2026, 8, 14),
+            ..FlightDetails::default()
+        };
+        let (gpass, report) = pass(PassCategory::Flight(flight)).to_gpass(None).unwrap();
+        let GoogleWalletClass::Flight(class) = &gpass.class else { panic!("not a flight") };
+        assert_eq!(class.local_scheduled_departure_date_time, None);
+        assert_eq!(report.warnings, [ConversionWarning::UnknownDepartureTime(chrono::NaiveDate::from_ymd_opt(2026, 8, 14).unwrap())]);
+
+        // Passes written before still read as dated.
+        let mut class = class.clone();
+        class.local_scheduled_departure_date_time = Some("2026-08-14".to_owned());
+        let gpass = GoogleWalletPass {
+            class: GoogleWalletClass::Flight(class),
+            object: gpass.object,
+        };
+        let (pass, report) = Pass::from_gpass(gpass);
+        assert!(report.is_lossless(), "{report:?}");
+        let PassCategory::Flight(flight) = pass.category else { panic!("not a flight") };
+        assert_eq!(flight.date, chrono::NaiveDate::from_ymd_opt(2026, 8, 14));
+        assert_eq!(flight.departure, None);
+    }
+
+    #[test]
+    fn flight_times_stay_local() {
+        let flight = FlightDetails {
+            departure: Some(local("2026-08-14T08:34:00-04:00")),
+            arrival: Some(local("2026-08-14T22:05")),
+            ..FlightDetails::default()
+        };
+        let gpass = pass(PassCategory::Flight(flight)).to_gpass(None).unwrap().0;
+        let GoogleWalletClass::Flight(class) = &gpass.class else { panic!("not a flight") };
+        assert_eq!(class.local_scheduled_departure_date_time.as_deref(), Some("2026-08-14T08:34:00"));
+        assert_eq!(class.local_scheduled_arrival_date_time.as_deref(), Some("2026-08-14T22:05:00"));
+
+        let (pass, _) = Pass::from_gpass(gpass);
+        let PassCategory::Flight(flight) = pass.category else { panic!("not a flight") };
+        assert_eq!(flight.departure, Some(local("2026-08-14T08:34:00")));
+        assert_eq!(flight.departure.unwrap().offset, None);
+    }
+
+    #[test]
+    fn event_times_keep_their_offset_only_when_given() {
+        let event = EventDetails {
+            start: Some(local("2026-10-18T19:30:00+02:00")),
+            end: Some(local("2026-10-18T22:00:00")),
+            ..EventDetails::default()
+        };
+        let gpass = pass(PassCategory::Event(event)).to_gpass(None).unwrap().0;
+        let GoogleWalletClass::EventTicket(class) = &gpass.class else { panic!("not an event") };
+        let date_time = class.date_time.as_ref().unwrap();
+        assert_eq!(date_time.start.as_deref(), Some("2026-10-18T19:30:00+02:00"));
+        assert_eq!(date_time.end.as_deref(), Some("2026-10-18T22:00:00"));
+    }
+
+    #[test]
+    fn local_validity_is_reported() {
+        let mut gpass = pass(PassCategory::Generic).to_gpass(None).unwrap().0;
+        let GoogleWalletObject::Generic(object) = &mut gpass.object else { panic!("not generic") };
+        object.common.valid_time_interval = Some(TimeInterval {
+            start: Some(DateTime { date: "2026-08-14T00:00:00".to_owned() }),
+            end: Some(DateTime { date: "2026-08-15T00:00:00Z".to_owned() }),
+        });
+
+        let (pass, report) = Pass::from_gpass(gpass);
+        assert_eq!(pass.dates.relevant, None);
+        assert_eq!(pass.dates.expiration.map(|d| d.to_rfc3339()).as_deref(), Some("2026-08-15T00:00:00+00:00"));
+        let paths: Vec<_> = report.dropped.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, ["genericObjects[0].validTimeInterval.start.date"]);
+    }
+
+    #[test]
+    fn images_are_linked_where_hosted() {
+        let mut with_logo = pass(PassCategory::Loyalty(LoyaltyDetails::default()));
+        with_logo.images.push(PassImage {
+            kind: PassImageKind::Logo,
+            scale: 2,
+            locale: None,
+            data: vec![],
+        });
+        assert!(matches!(with_logo.clone().to_gpass(Some("ftp://example.com")), Err(PassError::InvalidImageBaseUrl(_))));
+
+        let (gpass, report) = with_logo.clone().to_gpass(None).unwrap();
+        assert!(gpass.logo().is_none());
+        assert_eq!(report.warnings, [ConversionWarning::UnhostedImage("logo@2x.png".to_owned())]);
+
+        let (gpass, report) = with_logo.to_gpass(Some("https://example.com/passes/123/")).unwrap();
+        assert!(report.is_lossless());
+        assert_eq!(gpass.logo().unwrap().source_uri.uri, "https://example.com/passes/123/logo@2x.png");
+
+        // Reading it back would mean fetching the logo.
+        let (pass, report) = Pass::from_gpass(gpass);
+        assert!(pass.images.is_empty());
+        let paths: Vec<_> = report.dropped.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, ["loyaltyClasses[0].programLogo.sourceUri.uri"]);
+    }
+
+    #[test]
+    fn gpass_round_trip() {
+        let flight = FlightDetails {
+            carrier: Some("AC".to_owned()),
+            flight_number: Some("834".to_owned()),
+            origin: Some("YUL".to_owned()),
+            destination: Some("FRA".to_owned()),
+            departure: Some(local("2026-08-14T20:30:00")),
+            arrival: Some(local("2026-08-15T09:40:00")),
+            passenger_name: Some("Luc Desmarais".to_owned()),
+            seat: Some("1A".to_owned()),
+            confirmation_code: Some("ABC123".to_owned()),
+            ..FlightDetails::default()
+        };
+        let formats = [
+            (BarcodeFormat::Pdf417, "PDF_417"),
+            (BarcodeFormat::Aztec, "AZTEC"),
+            (BarcodeFormat::Qr, "QR_CODE"),
+            (BarcodeFormat::Code128, "CODE_128"),
+        ];
+        for category in [PassCategory::Flight(flight), PassCategory::Generic] {
+            for (format, kind) in formats {
+                let mut pass = pass(category.clone());
+                pass.barcodes.push(PassBarcode {
+                    format,
+                    message: "M1DESMARAIS/LUC       EABC123 YULFRAAC 0834 226F001A0025 100".to_owned(),
+                    encoding: "utf-8".to_owned(),
+                    alt_text: Some("ABC123".to_owned()),
+                });
+                let json = serde_json::to_value(pass.to_gpass(None).unwrap().0).unwrap();
+                let object = json.as_object().unwrap().values()
+                    .find_map(|objects| objects.get(0).filter(|o| o.get("classId").is_some()))
+                    .unwrap();
+                assert_eq!(object["barcode"]["type"], kind);
+
+                let gpass = GoogleWalletPass::read(json.to_string().as_bytes()).unwrap();
+                let (pass, report) = Pass::from_gpass(gpass);
+                assert!(report.is_lossless(), "{report:?}");
+                assert_eq!(pass.barcodes.iter().map(|b| (b.format, b.encoding.as_str())).collect::<Vec<_>>(), [(format, "utf-8")]);
+                assert_eq!(serde_json::to_value(pass.to_gpass(None).unwrap().0).unwrap(), json, "{kind}");
+            }
+        }
+    }
+
+    #[test]
+    fn unreadable_balances_are_reported() {
+        let gift_card = |amount: &str| pass(PassCategory::GiftCard(GiftCardDetails {
+            balance: Some(PassMoney {
+                amount: amount.to_owned(),
+                currency_code: "USD".to_owned(),
+            }),
+            ..GiftCardDetails::default()
+        }));
+        let balance = |gpass: &GoogleWalletPass| match &gpass.object {
+            GoogleWalletObject::GiftCard(object) => object.balance.as_ref().map(|b| b.micros),
+            _ => panic!("not a gift card"),
+        };
+
+        let (gpass, report) = gift_card("-1234.5").to_gpass(None).unwrap();
+        assert!(report.is_lossless(), "{report:?}");
+        assert_eq!(balance(&gpass), Some(-1_234_500_000));
+
+        for amount in ["1,234.50", "$25", "1e3", "0.1234567"] {
+            let (gpass, report) = gift_card(amount).to_gpass(None).unwrap();
+            assert_eq!(balance(&gpass), None, "{amount}");
+            assert_eq!(report.warnings, [ConversionWarning::InvalidBalance(amount.to_owned())]);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! The detached PKCS#7 `signature` of a pkpass, made over `manifest.json` with a Pass Type ID
+//! certificate issued by Apple's WWDR intermediate.
+
+use openssl::{
+    nid::Nid,
+    pkcs12::Pkcs12,
+    pkcs7::{Pkcs7, Pkcs7Flags},
+    pkey::{PKey, Private},
+    provider::Provider,
+    stack::Stack,
+    x509::{store::X509StoreBuilder, verify::X509VerifyFlags, X509, X509NameRef, X509PurposeId, X509Ref},
+};
+
+use super::{PassError, PkPass};
+
+/// Outcome of [`PkPass::verify_signature`], the first check that failed or else the identity the
+/// pass was signed with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureVerdict {
+    Valid {
+        pass_type_identifier: String,
+        team_identifier: String,
+    },
+    /// The pass has no `signature` or `manifest.json`.
+    Unsigned,
+    /// `signature` isn't a PKCS#7 signed data structure.
+    Malformed(String),
+    /// The signature doesn't cover `manifest.json` as it is.
+    ManifestMismatch(String),
+    /// The signing certificate doesn't chain to the WWDR certificates.
+    Untrusted(String),
+    /// The certificate is valid, but for another pass type or team.
+    IdentityMismatch {
+        certificate_pass_type_identifier: Option<String>,
+        certificate_team_identifier: Option<String>,
+    },
+}
+
+impl SignatureVerdict {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid { .. })
+    }
+}
+
+impl std::fmt::Display for SignatureVerdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Valid { pass_type_identifier, team_identifier } => write!(f, "signed for {pass_type_identifier} by team {team_identifier}"),
+            Self::Unsigned => write!(f, "not signed"),
+            Self::Malformed(e) => write!(f, "signature is malformed: {e}"),
+            Self::ManifestMismatch(e) => write!(f, "signature does not match manifest.json: {e}"),
+            Self::Untrusted(e) => write!(f, "signing certificate is not trusted: {e}"),
+            Self::IdentityMismatch { certificate_pass_type_identifier, certificate_team_identifier } => write!(
+                f,
+                "signing certificate is for pass type {certificate_pass_type_identifier:?} of team {certificate_team_identifier:?}",
+            ),
+        }
+    }
+}
+
+/// Reads every certificate of a PEM bundle, or a single DER certificate.
+pub fn read_certificates(data: &[u8]) -> Result<Vec<X509>, PassError> {
+    let certificates = if data.starts_with(b"-----") || data.windows(11).any(|w| w == b"-----BEGIN ") {
+        X509::stack_from_pem(data)?
+    } else {
+        vec![X509::from_der(data)?]
+    };
+    if certificates.is_empty() {
+        return Err(PassError::Certificate("No certificates found".to_owned()));
+    }
+
+    Ok(certificates)
+}
+
+fn name_entry(name: &X509NameRef, nid: Nid) -> Option<String> {
+    let entry = name.entries_by_nid(nid).next()?;
+    entry.data().to_string().ok()
+}
+
+/// Pass type and team of a Pass Type ID certificate, kept in its subject's user ID and
+/// organizational unit.
+pub fn certificate_identity(certificate: &X509Ref) -> (Option<String>, Option<String>) {
+    let subject = certificate.subject_name();
+    (name_entry(subject, Nid::USERID), name_entry(subject, Nid::ORGANIZATIONALUNITNAME))
+}
+
+/// A Pass Type ID certificate and its key, with the WWDR certificates to ship alongside them.
+pub struct Signer {
+    certificate: X509,
+    key: PKey<Private>,
+    wwdr: Vec<X509>,
+}
+
+impl Signer {
+    fn new(certificate: X509, key: PKey<Private>, wwdr: Vec<X509>) -> Result<Self, PassError> {
+        if !certificate.public_key()?.public_eq(&key) {
+            return Err(PassError::Certificate("Private key does not belong to the pass certificate".to_owned()));
+        }
+        if wwdr.is_empty() {
+            return Err(PassError::Certificate("Signing needs the WWDR certificate".to_owned()));
+        }
+
+        Ok(Self {
+            certificate,
+            key,
+            wwdr,
+        })
+    }
+
+    /// Reads the certificate and key from a `.p12`, as exported from Keychain Access.
+    pub fn from_pkcs12(data: &[u8], password: &str, wwdr: Vec<X509>) -> Result<Self, PassError> {
+        let pkcs12 = Pkcs12::from_der(data)?;
+        // Older exports encrypt with RC2, which OpenSSL 3 only has in its legacy provider.
+        let parsed = match pkcs12.parse2(password) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let Ok(_legacy) = Provider::try_load(None, "legacy", true) else {
+                    return Err(e.into());
+                };
+                pkcs12.parse2(password)?
+            },
+        };
+        let (Some(certificate), Some(key)) = (parsed.cert, parsed.pkey) else {
+            return Err(PassError::Certificate("PKCS#12 file does not hold a certificate and key".to_owned()));
+        };
+
+        Self::new(certificate, key, wwdr)
+    }
+
+    /// Reads the certificate and key from PEM, which may be the same file. `password` decrypts
+    /// an encrypted key.
+    pub fn from_pem(certificate: &[u8], key: &[u8], password: Option<&str>, wwdr: Vec<X509>) -> Result<Self, PassError> {
+        let certificate = X509::from_pem(certificate)?;
+        let key = match password {
+            Some(password) => PKey::private_key_from_pem_passphrase(key, password.as_bytes())?,
+            None => PKey::private_key_from_pem(key)?,
+        };
+
+        Self::new(certificate, key, wwdr)
+    }
+
+    /// Pass type and team the certificate was issued for.
+    pub fn identity(&self) -> (Option<String>, Option<String>) {
+        certificate_identity(&self.certificate)
+    }
+
+    /// Fails unless the certificate was issued for this pass type and team.
+    pub fn check_identity(&self, pass_type_identifier: &str, team_identifier: &str) -> Result<(), PassError> {
+        let (certificate_pass_type_identifier, certificate_team_identifier) = self.identity();
+        if certificate_pass_type_identifier.as_deref() != Some(pass_type_identifier) {
+            return Err(PassError::IdentityMismatch {
+                key: "passTypeIdentifier",
+                pass: pass_type_identifier.to_owned(),
+                certificate: certificate_pass_type_identifier,
+            });
+        }
+        if certificate_team_identifier.as_deref() != Some(team_identifier) {
+            return Err(PassError::IdentityMismatch {
+                key: "teamIdentifier",
+                pass: team_identifier.to_owned(),
+                certificate: certificate_team_identifier,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Makes the detached, DER encoded signature of `manifest`.
+    pub fn sign(&self, manifest: &[u8]) -> Result<Vec<u8>, PassError> {
+        let mut certificates = Stack::new()?;
+        for certificate in self.wwdr.iter() {
+            certificates.push(certificate.clone())?;
+        }
+        let pkcs7 = Pkcs7::sign(&self.certificate, &self.key, &certificates, manifest, Pkcs7Flags::BINARY | Pkcs7Flags::DETACHED)?;
+
+        Ok(pkcs7.to_der()?)
+    }
+}
+
+impl PkPass {
+    /// Checks the pass's signature against `wwdr`, the WWDR certificates to trust, and that it
+    /// was made for this pass type and team. Certificates are checked as of now.
+    pub fn verify_signature(&self, wwdr: &[X509]) -> Result<SignatureVerdict, PassError> {
+        let (Some(manifest), Some(signature)) = (self.manifest.as_deref(), self.signature.as_deref()) else {
+            return Ok(SignatureVerdict::Unsigned);
+        };
+        let pkcs7 = match Pkcs7::from_der(signature) {
+            Ok(pkcs7) => pkcs7,
+            Err(e) => return Ok(SignatureVerdict::Malformed(e.to_string())),
+        };
+
+        // WWDR is an intermediate, trusted without Apple's root. Pass certificates aren't S/MIME
+        // certificates, so any purpose goes.
+        let mut store = X509StoreBuilder::new()?;
+        for certificate in wwdr {
+            store.add_cert(certificate.clone())?;
+        }
+        store.set_flags(X509VerifyFlags::PARTIAL_CHAIN)?;
+        store.set_purpose(X509PurposeId::ANY)?;
+        let store = store.build();
+        let extra_certificates = Stack::new()?;
+
+        // Checking the signature before the chain tells a tampered manifest from an untrusted
+        // signer.
+        let content_only = Pkcs7Flags::BINARY | Pkcs7Flags::NOVERIFY;
+        if let Err(e) = pkcs7.verify(&extra_certificates, &store, Some(manifest), None, content_only) {
+            return Ok(SignatureVerdict::ManifestMismatch(e.to_string()));
+        }
+        if let Err(e) = pkcs7.verify(&extra_certificates, &store, Some(manifest), None, Pkcs7Flags::BINARY) {
+            return Ok(SignatureVerdict::Untrusted(e.to_string()));
+        }
+
+        let signers = pkcs7.signers(&extra_certificates, Pkcs7Flags::empty())?;
+        let Some(signer) = signers.iter().next() else {
+            return Ok(SignatureVerdict::Malformed("no signer certificate".to_owned()));
+        };
+        let (pass_type_identifier, team_identifier) = certificate_identity(signer);
+        match (pass_type_identifier, team_identifier) {
+            (Some(pass_type_identifier), Some(team_identifier))
+                if pass_type_identifier == self.base.pass_type_identifier && team_identifier == self.base.team_identifier => {
+                Ok(SignatureVerdict::Valid {
+                    pass_type_identifier,
+                    team_identifier,
+                })
+            },
+            (certificate_pass_type_identifier, certificate_team_identifier) => Ok(SignatureVerdict::IdentityMismatch {
+                certificate_pass_type_identifier,
+                certificate_team_identifier,
+            }),
+        }
+    }
+
+    /// Like [`Self::verify_signature`], but fails with [`PassError::BadSignature`] unless the
+    /// signature is valid.
+    pub fn check_signature(&self, wwdr: &[X509]) -> Result<SignatureVerdict, PassError> {
+        match self.verify_signature(wwdr)? {
+            verdict if verdict.is_valid() => Ok(verdict),
+            verdict => Err(PassError::BadSignature(verdict)),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Apple's `.strings` tables, lines of `"key" = "value";` in UTF-16 or UTF-8.
+
+use std::{collections::BTreeMap, iter::Peekable, str::Chars};
+
+/// Decodes `data`, going by its byte order mark, or by where the zero bytes of ASCII text fall
+/// when it has none.
+fn decode(data: &[u8]) -> Result<String, String> {
+    let (charset, data) = match data {
+        [0xef, 0xbb, 0xbf, rest @ ..] => (encoding_rs::UTF_8, rest),
+        [0xff, 0xfe, rest @ ..] => (encoding_rs::UTF_16LE, rest),
+        [0xfe, 0xff, rest @ ..] => (encoding_rs::UTF_16BE, rest),
+        [_, 0, ..] => (encoding_rs::UTF_16LE, data),
+        [0, _, ..] => (encoding_rs::UTF_16BE, data),
+        _ => (encoding_rs::UTF_8, data),
+    };
+    let Some(text) = charset.decode_without_bom_handling_and_without_replacement(data) else {
+        return Err(format!("not valid {}", charset.name()));
+    };
+
+    Ok(text.into_owned())
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// Skips whitespace and comments.
+    fn skip_trivia(&mut self) -> Result<(), String> {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.chars.next();
+                },
+                Some('/') => {
+                    self.chars.next();
+                    match self.chars.next() {
+                        Some('/') => {
+                            self.chars.by_ref().find(|&c| c == '\n');
+                        },
+                        Some('*') => {
+                            let mut prev = None;
+                            loop {
+                                match self.chars.next() {
+                                    Some('/') if prev == Some('*') => break,
+                                    Some(c) => prev = Some(c),
+                                    None => return Err("unterminated comment".to_owned()),
+                                }
+                            }
+                        },
+                        _ => return Err("stray '/'".to_owned()),
+                    }
+                },
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_trivia()?;
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("expected {expected:?}, found {c:?}")),
+            None => Err(format!("expected {expected:?}, found the end")),
+        }
+    }
+
+    /// Reads four hex digits of a `\U` escape.
+    fn hex_escape(&mut self) -> Result<u32, String> {
+        let digits: String = self.chars.by_ref().take(4).collect();
+        u32::from_str_radix(&digits, 16)
+            .map_err(|_| format!("invalid unicode escape {digits:?}"))
+    }
+
+    /// Reads a quoted string, or a bare word as old style property lists allow for keys.
+    fn string(&mut self) -> Result<String, String> {
+        self.skip_trivia()?;
+        if self.chars.peek() != Some(&'"') {
+            let mut word = String::new();
+            while let Some(&c) = self.chars.peek().filter(|c| c.is_alphanumeric() || "_.$:/-".contains(**c)) {
+                word.push(c);
+                self.chars.next();
+            }
+            if word.is_empty() {
+                return Err("expected a string".to_owned());
+            }
+            return Ok(word);
+        }
+
+        self.chars.next();
+        let mut s = String::new();
+        loop {
+            let Some(c) = self.chars.next() else {
+                return Err("unterminated string".to_owned());
+            };
+            match c {
+                '"' => return Ok(s),
+                '\\' => match self.chars.next() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('U' | 'u') => {
+                        let mut code = self.hex_escape()?;
+                        // Characters outside the BMP are escaped as a surrogate pair.
+                        if (0xd800..0xdc00).contains(&code) && self.chars.next_if_eq(&'\\').is_some() {
+                            self.chars.next();
+                            let low = self.hex_escape()?;
+                            code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                        }
+                        let Some(c) = char::from_u32(code) else {
+                            return Err(format!("invalid unicode escape {code:#x}"));
+                        };
+                        s.push(c);
+                    },
+                    // Quotes, backslashes and anything else stand for themselves.
+                    Some(c) => s.push(c),
+                    None => return Err("unterminated string".to_owned()),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+}
+
+/// Parses a `.strings` file into its table.
+pub fn parse(data: &[u8]) -> Result<BTreeMap<String, String>, String> {
+    let text = decode(data)?;
+    let mut parser = Parser {
+        chars: text.chars().peekable(),
+    };
+
+    let mut strings = BTreeMap::new();
+    loop {
+        parser.skip_trivia()?;
+        if parser.chars.peek().is_none() {
+            break;
+        }
+        let key = parser.string()?;
+        parser.skip_trivia()?;
+        // `"key";` on its own maps the key to itself.
+        let value = if parser.chars.next_if_eq(&'=').is_some() {
+            parser.string()?
+        } else {
+            key.clone()
+        };
+        parser.expect(';')?;
+        strings.insert(key, value);
+    }
+
+    Ok(strings)
+}
+
+/// Writes `strings` as a UTF-8 `.strings` file.
+pub fn to_bytes(strings: &BTreeMap<String, String>) -> Vec<u8> {
+    let quote = |s: &str| {
+        let mut quoted = String::with_capacity(s.len() + 2);
+        quoted.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                '\n' => quoted.push_str("\\n"),
+                '\t' => quoted.push_str("\\t"),
+                '\r' => quoted.push_str("\\r"),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    };
+
+    strings.iter()
+        .map(|(key, value)| format!("{} = {};\n", quote(key), quote(value)))
+        .collect::<String>()
+        .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, big_endian: bool, bom: bool) -> Vec<u8> {
+        bom.then_some('\u{feff}').into_iter()
+            .chain(text.chars())
+            .collect::<String>()
+            .encode_utf16()
+            .flat_map(|unit| if big_endian { unit.to_be_bytes() } else { unit.to_le_bytes() })
+            .collect()
+    }
+
+    const TABLE: &str = "/* Greeting\n   on the front */\n\"greeting\" = \"Bonjour\";\n// Shown as is.\n\"Gate\";\nterms_url = \"https://example.com\";\n";
+
+    #[test]
+    fn utf16_with_and_without_bom() {
+        let expected = BTreeMap::from([
+            ("Gate".to_owned(), "Gate".to_owned()),
+            ("greeting".to_owned(), "Bonjour".to_owned()),
+            ("terms_url".to_owned(), "https://example.com".to_owned()),
+        ]);
+        assert_eq!(parse(TABLE.as_bytes()).unwrap(), expected);
+        for (big_endian, bom) in [(false, true), (true, true), (false, false), (true, false)] {
+            assert_eq!(parse(&utf16(TABLE, big_endian, bom)).unwrap(), expected, "big endian {big_endian}, BOM {bom}");
+        }
+
+        let mut with_bom = vec![0xef, 0xbb, 0xbf];
+        with_bom.extend_from_slice(TABLE.as_bytes());
+        assert_eq!(parse(&with_bom).unwrap(), expected);
+    }
+
+    #[test]
+    fn escapes() {
+        let strings = parse(br#""quote \"q\"" = "a\nb\t\\ \U00e9 \UD83D\UDE00";"#).unwrap();
+        assert_eq!(strings["quote \"q\""], "a\nb\t\\ \u{e9} \u{1f600}");
+    }
+
+    #[test]
+    fn invalid_tables() {
+        for table in [
+            r#""a" = "b""#,
+            r#""a" = "b"#,
+            r#"/* "a" = "b";"#,
+            r#""a" / "b";"#,
+            r#""a" = "\UD83D";"#,
+            r#""a" = "\U12";"#,
+        ] {
+            assert!(parse(table.as_bytes()).is_err(), "{table}");
+        }
+        assert!(parse(&[0xff, 0xfe, 0x00, 0xd8]).is_err());
+    }
+
+    #[test]
+    fn written_tables_read_back() {
+        let strings = BTreeMap::from([
+            ("Seat \"%@\"".to_owned(), "Siège \"%@\"\n\\ 😀".to_owned()),
+            ("Gate".to_owned(), "Porte".to_owned()),
+        ]);
+        assert_eq!(parse(&to_bytes(&strings)).unwrap(), strings);
+    }
+}